@@ -0,0 +1,46 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use roblox_buffer::{Buffer, BufferEncoding};
+
+const SIZES: [usize; 4] = [16, 1024, 64 * 1024, 1024 * 1024];
+
+fn sample(len: usize) -> Buffer {
+    // Somewhat compressible, like most real data.
+    (0..len)
+        .map(|i| (i % 251) as u8 ^ (i / 4096) as u8)
+        .collect()
+}
+
+fn to_json(buffer: &Buffer, encoding: BufferEncoding) -> String {
+    let mut json = Vec::new();
+    buffer
+        .serialize_with_encoding(encoding, &mut serde_json::Serializer::new(&mut json))
+        .unwrap();
+    String::from_utf8(json).unwrap()
+}
+
+fn bench(c: &mut Criterion) {
+    for (name, encoding) in [
+        ("base64", BufferEncoding::Base64),
+        ("zbase64", BufferEncoding::ZBase64),
+    ] {
+        let mut group = c.benchmark_group(name);
+
+        for len in SIZES {
+            let buffer = sample(len);
+            let json = to_json(&buffer, encoding);
+
+            group.bench_with_input(BenchmarkId::new("encode", len), &buffer, |b, buffer| {
+                b.iter(|| to_json(buffer, encoding))
+            });
+
+            group.bench_with_input(BenchmarkId::new("decode", len), &json, |b, json| {
+                b.iter(|| serde_json::from_str::<Buffer>(json).unwrap())
+            });
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
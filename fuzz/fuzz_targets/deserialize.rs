@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use roblox_buffer::Buffer;
+
+fuzz_target!(|data: &str| {
+    // Must only ever return an error, never panic.
+    let _ = serde_json::from_str::<Buffer>(data);
+});
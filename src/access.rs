@@ -0,0 +1,291 @@
+use std::ops::Range;
+
+use crate::Buffer;
+
+/// Returned when accessing bytes past the end of a [`Buffer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds {
+    /// The offset that was accessed.
+    pub offset: usize,
+    /// The number of bytes that were accessed.
+    pub len: usize,
+    /// The length of the buffer.
+    pub buffer_len: usize,
+}
+
+impl std::fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "tried to access {} bytes at offset {}, but the buffer is only {} bytes long",
+            self.len, self.offset, self.buffer_len
+        )
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+pub(crate) fn checked_range(
+    offset: usize,
+    len: usize,
+    buffer_len: usize,
+) -> Result<Range<usize>, OutOfBounds> {
+    match offset.checked_add(len) {
+        Some(end) if end <= buffer_len => Ok(offset..end),
+        _ => Err(OutOfBounds {
+            offset,
+            len,
+            buffer_len,
+        }),
+    }
+}
+
+macro_rules! typed_access {
+    ($($ty:ty, $read:ident, $write:ident, $append:ident;)*) => {
+        impl<T: AsRef<[u8]>> Buffer<T> {
+            $(
+                #[doc = concat!("Reads a little endian `", stringify!($ty), "` at the given offset, like `buffer.", stringify!($read), "` in Roblox.")]
+                pub fn $read(&self, offset: usize) -> Result<$ty, OutOfBounds> {
+                    let bytes = self.as_ref();
+                    let range = checked_range(offset, size_of::<$ty>(), bytes.len())?;
+                    Ok(<$ty>::from_le_bytes(bytes[range].try_into().unwrap()))
+                }
+            )*
+        }
+
+        impl Buffer {
+            $(
+                #[doc = concat!("Writes a little endian `", stringify!($ty), "` at the given offset, like `buffer.", stringify!($write), "` in Roblox.")]
+                pub fn $write(&mut self, offset: usize, value: $ty) -> Result<(), OutOfBounds> {
+                    let range = checked_range(offset, size_of::<$ty>(), self.0.len())?;
+                    self.0[range].copy_from_slice(&value.to_le_bytes());
+                    Ok(())
+                }
+
+                #[doc = concat!("Appends a little endian `", stringify!($ty), "` to the end of the buffer, returning the offset it was written at.")]
+                pub fn $append(&mut self, value: $ty) -> usize {
+                    let offset = self.0.len();
+                    self.0.extend_from_slice(&value.to_le_bytes());
+                    offset
+                }
+            )*
+        }
+    };
+}
+
+typed_access! {
+    u8, read_u8, write_u8, append_u8;
+    i8, read_i8, write_i8, append_i8;
+    u16, read_u16, write_u16, append_u16;
+    i16, read_i16, write_i16, append_i16;
+    u32, read_u32, write_u32, append_u32;
+    i32, read_i32, write_i32, append_i32;
+    f32, read_f32, write_f32, append_f32;
+    f64, read_f64, write_f64, append_f64;
+}
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Returns the byte at the given index, or `None` if it is past the end of the buffer.
+    pub fn byte_at(&self, index: usize) -> Option<u8> {
+        self.as_ref().get(index).copied()
+    }
+
+    /// Returns the bit at the given index, or `None` if it is past the end of the buffer.
+    /// Bits are numbered from the least significant bit of each byte first, the same as `buffer.readbits` in Roblox.
+    pub fn get_bit(&self, bit_index: usize) -> Option<bool> {
+        let byte = self.byte_at(bit_index / 8)?;
+        Some(byte & (1 << (bit_index % 8)) != 0)
+    }
+
+    /// Reads `len` bytes at the given offset, like `buffer.readstring` in Roblox.
+    pub fn read_string(&self, offset: usize, len: usize) -> Result<&[u8], OutOfBounds> {
+        let bytes = self.as_ref();
+        let range = checked_range(offset, len, bytes.len())?;
+        Ok(&bytes[range])
+    }
+}
+
+impl Buffer {
+    /// Sets the byte at the given index, returning an error if it is past the end of the buffer.
+    pub fn set_byte(&mut self, index: usize, value: u8) -> Result<(), OutOfBounds> {
+        let buffer_len = self.0.len();
+        let byte = self.0.get_mut(index).ok_or(OutOfBounds {
+            offset: index,
+            len: 1,
+            buffer_len,
+        })?;
+
+        *byte = value;
+        Ok(())
+    }
+
+    /// Sets the bit at the given index, numbered the same as [`Buffer::get_bit`], returning an error if it is past
+    /// the end of the buffer.
+    pub fn set_bit(&mut self, bit_index: usize, value: bool) -> Result<(), OutOfBounds> {
+        let buffer_len = self.0.len();
+        let byte = self.0.get_mut(bit_index / 8).ok_or(OutOfBounds {
+            offset: bit_index / 8,
+            len: 1,
+            buffer_len,
+        })?;
+
+        let mask = 1 << (bit_index % 8);
+        if value {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+
+        Ok(())
+    }
+
+    /// Copies bytes over the buffer at the given offset without growing it, like `buffer.writestring` in Roblox.
+    pub fn overwrite(&mut self, offset: usize, bytes: &[u8]) -> Result<(), OutOfBounds> {
+        let range = checked_range(offset, bytes.len(), self.0.len())?;
+        self.0[range].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Appends bytes to the end of the buffer, returning the offset they were written at.
+    pub fn append_string(&mut self, bytes: impl AsRef<[u8]>) -> usize {
+        let offset = self.0.len();
+        self.0.extend_from_slice(bytes.as_ref());
+        offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_access() {
+        let mut buffer = Buffer::new(vec![0; 16]);
+        buffer.write_u32(1, 0xdeadbeef).unwrap();
+        buffer.write_i16(5, -2).unwrap();
+        buffer.write_f64(8, 1.5).unwrap();
+
+        assert_eq!(buffer.read_u32(1), Ok(0xdeadbeef));
+        assert_eq!(buffer.read_u8(1), Ok(0xef));
+        assert_eq!(buffer.read_i16(5), Ok(-2));
+        assert_eq!(buffer.read_u16(5), Ok(0xfffe));
+        assert_eq!(buffer.read_f64(8), Ok(1.5));
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let mut buffer = Buffer::new(vec![0; 6]);
+
+        let error = buffer.read_u32(3).unwrap_err();
+        assert_eq!(
+            error,
+            OutOfBounds {
+                offset: 3,
+                len: 4,
+                buffer_len: 6,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "tried to access 4 bytes at offset 3, but the buffer is only 6 bytes long"
+        );
+
+        assert_eq!(
+            buffer.write_f64(usize::MAX, 0.0),
+            Err(OutOfBounds {
+                offset: usize::MAX,
+                len: 8,
+                buffer_len: 6,
+            })
+        );
+        assert_eq!(buffer, Buffer::new(vec![0; 6]));
+    }
+
+    #[test]
+    fn test_append() {
+        let mut buffer = Buffer::new(vec![0xaa]);
+
+        let u32_offset = buffer.append_u32(0xdeadbeef);
+        let f64_offset = buffer.append_f64(-0.25);
+        let string_offset = buffer.append_string("hello");
+        let i8_offset = buffer.append_i8(-1);
+
+        assert_eq!(
+            (u32_offset, f64_offset, string_offset, i8_offset),
+            (1, 5, 13, 18)
+        );
+        assert_eq!(buffer.as_ref().len(), 19);
+
+        assert_eq!(buffer.read_u32(u32_offset), Ok(0xdeadbeef));
+        assert_eq!(buffer.read_f64(f64_offset), Ok(-0.25));
+        assert_eq!(buffer.read_string(string_offset, 5), Ok(&b"hello"[..]));
+        assert_eq!(buffer.read_i8(i8_offset), Ok(-1));
+        assert!(buffer.read_string(string_offset, 7).is_err());
+    }
+
+    #[test]
+    fn test_byte_at() {
+        let mut buffer = Buffer::new(vec![1, 2, 3]);
+
+        assert_eq!(buffer.byte_at(0), Some(1));
+        assert_eq!(buffer.byte_at(2), Some(3));
+        assert_eq!(buffer.byte_at(3), None);
+
+        buffer.set_byte(1, 0xff).unwrap();
+        assert_eq!(buffer.byte_at(1), Some(0xff));
+        assert_eq!(
+            buffer.set_byte(3, 0),
+            Err(OutOfBounds {
+                offset: 3,
+                len: 1,
+                buffer_len: 3,
+            })
+        );
+        assert_eq!(buffer, Buffer::new(vec![1, 0xff, 3]));
+    }
+
+    #[test]
+    fn test_overwrite() {
+        let mut buffer = Buffer::new("hello world");
+
+        buffer.overwrite(6, b"there").unwrap();
+        assert_eq!(buffer, Buffer::new("hello there"));
+
+        buffer.overwrite(11, b"").unwrap();
+        assert_eq!(
+            buffer.overwrite(8, b"!!!!"),
+            Err(OutOfBounds {
+                offset: 8,
+                len: 4,
+                buffer_len: 11,
+            })
+        );
+        assert_eq!(buffer, Buffer::new("hello there"));
+    }
+
+    #[test]
+    fn test_bits() {
+        let mut buffer = Buffer::new([0b0000_0101, 0]);
+
+        assert_eq!(buffer.get_bit(0), Some(true));
+        assert_eq!(buffer.get_bit(1), Some(false));
+        assert_eq!(buffer.get_bit(2), Some(true));
+        assert_eq!(buffer.get_bit(16), None);
+
+        buffer.set_bit(0, false).unwrap();
+        buffer.set_bit(9, true).unwrap();
+        buffer.set_bit(15, true).unwrap();
+        assert_eq!(buffer, Buffer::new([0b0000_0100, 0b1000_0010]));
+        assert_eq!(buffer.get_bit(9), Some(true));
+        assert_eq!(buffer.get_bit(0), Some(false));
+
+        assert_eq!(
+            buffer.set_bit(16, true),
+            Err(OutOfBounds {
+                offset: 2,
+                len: 1,
+                buffer_len: 2,
+            })
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use crate::Buffer;
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Returns how many times each byte value appears in the buffer, indexed by the byte value.
+    pub fn byte_histogram(&self) -> [u64; 256] {
+        let mut counts = [0; 256];
+
+        for &byte in self.as_ref() {
+            counts[byte as usize] += 1;
+        }
+
+        counts
+    }
+
+    /// Returns the CRC-32 (IEEE) checksum of the contents, the same as zlib and most archive formats use.
+    pub fn crc32(&self) -> u32 {
+        crc32fast::hash(self.as_ref())
+    }
+
+    /// Returns the same checksum as [`Buffer::crc32`], computed in parallel chunks. Enabled with the `rayon` feature.
+    /// Only worth it for large buffers, of at least several megabytes.
+    #[cfg(feature = "rayon")]
+    pub fn par_crc32(&self) -> u32 {
+        use rayon::prelude::*;
+
+        const CHUNK_LEN: usize = 1024 * 1024;
+
+        self.as_ref()
+            .par_chunks(CHUNK_LEN)
+            .map(|chunk| {
+                let mut hasher = crc32fast::Hasher::new();
+                hasher.update(chunk);
+                hasher
+            })
+            .reduce(crc32fast::Hasher::new, |mut left, right| {
+                left.combine(&right);
+                left
+            })
+            .finalize()
+    }
+
+    /// Returns a fast, non-cryptographic 64 bit hash of the contents, using XXH3.
+    ///
+    /// This is only for deduplicating and detecting changes, such as a short DataStore key derived from the value.
+    /// It is easy to find collisions on purpose, so don't use it to identify untrusted contents.
+    /// The hash is stable across versions of this crate.
+    #[cfg(feature = "twox-hash")]
+    pub fn short_hash(&self) -> u64 {
+        twox_hash::XxHash3_64::oneshot(self.as_ref())
+    }
+
+    /// Returns the number of bits set in the buffer.
+    pub fn count_ones(&self) -> u64 {
+        self.as_ref()
+            .iter()
+            .map(|byte| u64::from(byte.count_ones()))
+            .sum()
+    }
+
+    /// Returns the number of bits not set in the buffer.
+    pub fn count_zeros(&self) -> u64 {
+        self.as_ref().len() as u64 * 8 - self.count_ones()
+    }
+
+    /// Returns the Shannon entropy of the bytes in the buffer, in bits per byte.
+    /// Data close to 8.0 is unlikely to compress, such as data that is already compressed.
+    pub fn shannon_entropy(&self) -> f64 {
+        let len = self.as_ref().len() as f64;
+
+        self.byte_histogram()
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let probability = count as f64 / len;
+                -probability * probability.log2()
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_histogram() {
+        let histogram = Buffer::new("aaab").byte_histogram();
+        assert_eq!(histogram[b'a' as usize], 3);
+        assert_eq!(histogram[b'b' as usize], 1);
+        assert_eq!(histogram.iter().sum::<u64>(), 4);
+
+        assert_eq!(Buffer::default().byte_histogram(), [0; 256]);
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(Buffer::default().crc32(), 0);
+        assert_eq!(Buffer::new("123456789").crc32(), 0xcbf4_3926);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_crc32() {
+        let noise = Buffer::new(crate::tests::random_bytes(10 * 1024 * 1024 + 123));
+
+        assert_eq!(noise.par_crc32(), noise.crc32());
+        assert_eq!(noise.view(..1000).par_crc32(), noise.view(..1000).crc32());
+        assert_eq!(Buffer::default().par_crc32(), 0);
+    }
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(Buffer::default().shannon_entropy(), 0.0);
+        assert_eq!(Buffer::new(vec![0; 1000]).shannon_entropy(), 0.0);
+        assert_eq!(Buffer::new("abab").shannon_entropy(), 1.0);
+
+        let uniform: Buffer = (0..=255).cycle().take(256 * 16).collect();
+        assert!((uniform.shannon_entropy() - 8.0).abs() < 1e-9);
+
+        let mut state = 1u32;
+        let noise: Buffer = (0..64 * 1024)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect();
+        assert!(noise.shannon_entropy() > 7.99);
+    }
+
+    #[cfg(feature = "twox-hash")]
+    #[test]
+    fn test_short_hash() {
+        let buffer = Buffer::new("hello world");
+        assert_eq!(buffer.short_hash(), buffer.short_hash());
+        assert_eq!(buffer.short_hash(), buffer.view(..).short_hash());
+        assert_ne!(buffer.short_hash(), Buffer::new("hello worle").short_hash());
+
+        // Reference value for XXH3 with no seed.
+        assert_eq!(Buffer::default().short_hash(), 0x2d06_8005_38d3_94c2);
+    }
+
+    #[test]
+    fn test_count_ones() {
+        let buffer = Buffer::new([0b1010_1010, 0xff, 0, 0b0000_0001]);
+
+        assert_eq!(buffer.count_ones(), 4 + 8 + 1);
+        assert_eq!(buffer.count_zeros(), 32 - 13);
+        assert_eq!(Buffer::default().count_ones(), 0);
+        assert_eq!(Buffer::default().count_zeros(), 0);
+    }
+}
@@ -0,0 +1,60 @@
+//! Serializes bytes as a Roblox buffer using the `base64` encoding, for use with `#[serde(with = "roblox_buffer::as_base64")]`.
+//! Deserializing accepts any encoding [`Buffer`] does.
+//!
+//! ```
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Save {
+//!     #[serde(with = "roblox_buffer::as_base64")]
+//!     data: Vec<u8>,
+//! }
+//! ```
+
+use serde::{Deserialize, Deserializer, Serializer};
+
+use crate::{Buffer, BufferEncoding, EncodeOptions};
+
+/// Serializes bytes as a Roblox buffer using the `base64` encoding.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]> + ?Sized,
+    S: Serializer,
+{
+    crate::encode::serialize_bytes(
+        value.as_ref(),
+        &EncodeOptions::new(BufferEncoding::Base64),
+        serializer,
+    )
+}
+
+/// Deserializes a Roblox buffer into bytes.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Buffer::deserialize(deserializer).map(Buffer::into_vec)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Save {
+        #[serde(with = "crate::as_base64")]
+        data: Vec<u8>,
+    }
+
+    #[test]
+    fn test_as_base64() {
+        let save = Save {
+            data: b"hello world".to_vec(),
+        };
+
+        let json = serde_json::to_string(&save).unwrap();
+        assert_eq!(
+            json,
+            r#"{"data":{"m":null,"t":"buffer","base64":"aGVsbG8gd29ybGQ="}}"#
+        );
+        assert_eq!(serde_json::from_str::<Save>(&json).unwrap(), save);
+    }
+}
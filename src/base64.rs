@@ -0,0 +1,491 @@
+use std::io::{self, Read};
+
+use data_encoding::{BASE64, DecodeError};
+
+use crate::{Buffer, BufferError};
+
+/// Returned when base64 fails to decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64Error(DecodeError);
+
+impl Base64Error {
+    /// Returns the position of the invalid character in the base64 string.
+    pub fn position(&self) -> usize {
+        self.0.position
+    }
+}
+
+impl From<DecodeError> for Base64Error {
+    fn from(error: DecodeError) -> Self {
+        Self(error)
+    }
+}
+
+impl std::fmt::Display for Base64Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid base64: {}", self.0)
+    }
+}
+
+impl std::error::Error for Base64Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl Buffer {
+    /// Validates base64 and returns the number of bytes it decodes to, without decoding it into memory.
+    /// Accepts exactly the same strings as deserializing a buffer does.
+    pub fn base64_decoded_len(b64: &str) -> Result<usize, Base64Error> {
+        // Multiple of 4 so that chunks line up with base64 blocks.
+        const CHUNK_LEN: usize = 4 * 256;
+
+        let input = b64.as_bytes();
+        BASE64.decode_len(input.len())?;
+
+        let mut output = [0; CHUNK_LEN / 4 * 3];
+        let mut len = 0;
+
+        for (index, chunk) in input.chunks(CHUNK_LEN).enumerate() {
+            let output = &mut output[..BASE64.decode_len(chunk.len())?];
+            len += BASE64
+                .decode_mut(chunk, output)
+                .map_err(|partial| DecodeError {
+                    position: index * CHUNK_LEN + partial.error.position,
+                    ..partial.error
+                })?;
+        }
+
+        Ok(len)
+    }
+}
+
+/// Decodes base64 onto the end of `data`, reserving space for it all up front so that `data` only grows once.
+/// `data` is left unchanged if the base64 is invalid.
+fn decode_onto(input: &[u8], data: &mut Vec<u8>) -> Result<(), DecodeError> {
+    let start = data.len();
+    // At most 2 bytes more than needed, for padding.
+    data.resize(start + BASE64.decode_len(input.len())?, 0);
+
+    match BASE64.decode_mut(input, &mut data[start..]) {
+        Ok(len) => {
+            data.truncate(start + len);
+            Ok(())
+        }
+
+        Err(partial) => {
+            data.truncate(start);
+            Err(partial.error)
+        }
+    }
+}
+
+/// Decodes base64 with a single allocation.
+pub(crate) fn decode_base64(input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let mut data = Vec::with_capacity(BASE64.decode_len(input.len())?);
+    decode_onto(input, &mut data)?;
+    Ok(data)
+}
+
+impl Buffer {
+    /// Decodes base64, without the Roblox envelope, onto the end of the buffer.
+    /// The buffer is left unchanged if the base64 is invalid.
+    pub fn append_base64(&mut self, b64: &str) -> Result<(), Base64Error> {
+        Ok(decode_onto(b64.as_bytes(), &mut self.0)?)
+    }
+
+    /// Decodes base64 read from `reader`, without the Roblox envelope, a chunk at a time.
+    /// Fails with [`BufferError::LimitExceeded`] if the base64 decodes to more than `max` bytes.
+    pub fn decode_base64_reader<R: Read>(mut reader: R, max: usize) -> Result<Buffer, BufferError> {
+        // Multiple of 4 so that chunks line up with base64 blocks.
+        const CHUNK_LEN: usize = 4 * 256;
+
+        let mut input = [0; CHUNK_LEN];
+        let mut input_len = 0;
+        let mut position = 0;
+        let mut data = Vec::new();
+
+        loop {
+            let read = match reader.read(&mut input[input_len..]) {
+                Ok(read) => read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            };
+
+            input_len += read;
+
+            // Decode whole blocks, keeping the rest for when more is read.
+            let blocks_len = if read == 0 {
+                input_len
+            } else {
+                input_len / 4 * 4
+            };
+
+            let blocks = &input[..blocks_len];
+            let decoded_len = BASE64
+                .decode_len(blocks.len())
+                .map_err(|error| invalid_base64(error, position))?;
+
+            let start = data.len();
+            data.resize(start + decoded_len, 0);
+            let written = BASE64
+                .decode_mut(blocks, &mut data[start..])
+                .map_err(|partial| invalid_base64(partial.error, position))?;
+            data.truncate(start + written);
+
+            // Checked after every chunk, so at most a chunk more than `max` is ever decoded.
+            if data.len() > max {
+                return Err(BufferError::LimitExceeded { max });
+            }
+
+            if read == 0 {
+                return Ok(Buffer::new(data));
+            }
+
+            input.copy_within(blocks_len..input_len, 0);
+            input_len -= blocks_len;
+            position += blocks_len;
+        }
+    }
+}
+
+/// Decodes base64, without the Roblox envelope, as it arrives a piece at a time.
+///
+/// ```
+/// # use roblox_buffer::{Base64Accumulator, Buffer};
+/// let buffer = Base64Accumulator::new()
+///     .push_str("aGVsbG")
+///     .push_str("8gd29ybGQ=")
+///     .finish()
+///     .unwrap();
+/// assert_eq!(buffer, Buffer::new("hello world"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Base64Accumulator {
+    data: Vec<u8>,
+    // Characters that don't make up a full base64 block yet.
+    pending: [u8; 4],
+    pending_len: usize,
+    // How many characters have been decoded, for the positions of errors.
+    position: usize,
+    error: Option<Base64Error>,
+}
+
+impl Base64Accumulator {
+    /// Creates an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds more base64. Errors are returned by [`Base64Accumulator::finish`], and stop anything after them from
+    /// being decoded.
+    pub fn push_str(mut self, b64: &str) -> Self {
+        let mut input = b64.as_bytes();
+
+        while self.error.is_none() && !input.is_empty() {
+            if self.pending_len > 0 || input.len() < 4 {
+                let take = (4 - self.pending_len).min(input.len());
+                self.pending[self.pending_len..self.pending_len + take]
+                    .copy_from_slice(&input[..take]);
+                self.pending_len += take;
+                input = &input[take..];
+
+                if self.pending_len == 4 {
+                    let pending = self.pending;
+                    self.decode_blocks(&pending);
+                    self.pending_len = 0;
+                }
+            } else {
+                let (blocks, rest) = input.split_at(input.len() / 4 * 4);
+                self.decode_blocks(blocks);
+                input = rest;
+            }
+        }
+
+        self
+    }
+
+    /// Decodes what's left, returning the buffer or the first error.
+    pub fn finish(mut self) -> Result<Buffer, Base64Error> {
+        if self.error.is_none() && self.pending_len > 0 {
+            let pending = self.pending;
+            self.decode_blocks(&pending[..self.pending_len]);
+        }
+
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(Buffer::new(self.data)),
+        }
+    }
+
+    fn decode_blocks(&mut self, blocks: &[u8]) {
+        let result = BASE64.decode_len(blocks.len()).and_then(|decoded_len| {
+            let start = self.data.len();
+            self.data.resize(start + decoded_len, 0);
+
+            let written = BASE64
+                .decode_mut(blocks, &mut self.data[start..])
+                .map_err(|partial| partial.error)?;
+            self.data.truncate(start + written);
+
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => self.position += blocks.len(),
+            Err(error) => {
+                self.error = Some(Base64Error(DecodeError {
+                    position: self.position + error.position,
+                    kind: error.kind,
+                }));
+            }
+        }
+    }
+}
+
+// Multiple of 4 so that chunks line up with base64 blocks.
+#[cfg(feature = "json")]
+const READER_CHUNK_LEN: usize = 4 * 256;
+
+/// Reads the bytes that base64, without the Roblox envelope, decodes to, decoding a chunk at a time.
+/// Invalid base64 is returned as an [`io::ErrorKind::InvalidData`] error holding a [`BufferError`].
+#[cfg(feature = "json")]
+pub(crate) struct Base64Reader<'a> {
+    input: &'a [u8],
+    position: usize,
+    output: [u8; READER_CHUNK_LEN / 4 * 3],
+    output_start: usize,
+    output_end: usize,
+}
+
+#[cfg(feature = "json")]
+impl<'a> Base64Reader<'a> {
+    pub(crate) fn new(b64: &'a str) -> Self {
+        Self {
+            input: b64.as_bytes(),
+            position: 0,
+            output: [0; READER_CHUNK_LEN / 4 * 3],
+            output_start: 0,
+            output_end: 0,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl Read for Base64Reader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.output_start == self.output_end {
+            let chunk = &self.input[self.position..];
+            let chunk = &chunk[..chunk.len().min(READER_CHUNK_LEN)];
+
+            let invalid = |error| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    invalid_base64(error, self.position),
+                )
+            };
+
+            let output = &mut self.output[..BASE64.decode_len(chunk.len()).map_err(invalid)?];
+            self.output_end = BASE64
+                .decode_mut(chunk, output)
+                .map_err(|partial| invalid(partial.error))?;
+            self.output_start = 0;
+            self.position += chunk.len();
+        }
+
+        let output = &self.output[self.output_start..self.output_end];
+        let len = output.len().min(buf.len());
+        buf[..len].copy_from_slice(&output[..len]);
+        self.output_start += len;
+
+        Ok(len)
+    }
+}
+
+fn invalid_base64(error: DecodeError, offset: usize) -> BufferError {
+    DecodeError {
+        position: offset + error.position,
+        kind: error.kind,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use data_encoding::DecodeKind;
+
+    use super::*;
+
+    #[test]
+    fn test_base64_decoded_len() {
+        assert_eq!(Buffer::base64_decoded_len("").unwrap(), 0);
+        assert_eq!(Buffer::base64_decoded_len("aGk=").unwrap(), 2);
+        assert_eq!(Buffer::base64_decoded_len("aGVsbG8gd29ybGQ=").unwrap(), 11);
+
+        let long = Buffer::new(vec![7; 5000]).to_base64();
+        assert_eq!(Buffer::base64_decoded_len(&long).unwrap(), 5000);
+    }
+
+    #[test]
+    fn test_base64_decoded_len_invalid() {
+        for invalid in ["aGk", "aG=k", "aGl=", "aGk= ", "aGVsbG8gd29ybGQ!"] {
+            assert!(
+                matches!(
+                    Buffer::base64_decoded_len(invalid),
+                    Err(error)
+                        if error == Base64Error(BASE64.decode(invalid.as_bytes()).unwrap_err())
+                ),
+                "{invalid}"
+            );
+        }
+
+        let mut long = Buffer::new(vec![7; 5000]).to_base64();
+        long.replace_range(3000..3001, "*");
+        assert!(matches!(
+            Buffer::base64_decoded_len(&long),
+            Err(error) if error.position() == 3000
+        ));
+    }
+
+    /// Reads at most a few bytes at a time.
+    struct SmallChunks<'a>(&'a [u8]);
+
+    impl Read for SmallChunks<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(3);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_decode_base64_reader() {
+        assert_eq!(
+            Buffer::decode_base64_reader(SmallChunks(b"aGVsbG8gd29ybGQ="), 100).unwrap(),
+            Buffer::new("hello world")
+        );
+
+        let long = Buffer::new((0..=255).cycle().take(5000).collect::<Vec<u8>>());
+        let base64 = long.to_base64();
+        assert_eq!(
+            Buffer::decode_base64_reader(SmallChunks(base64.as_bytes()), 5000).unwrap(),
+            long
+        );
+        assert_eq!(
+            Buffer::decode_base64_reader(base64.as_bytes(), 5000).unwrap(),
+            long
+        );
+        assert_eq!(
+            Buffer::decode_base64_reader(&b""[..], 0).unwrap(),
+            Buffer::default()
+        );
+    }
+
+    #[test]
+    fn test_decode_base64_reader_invalid() {
+        let error = Buffer::decode_base64_reader(SmallChunks(b"aGVsbG8gd29ybGQ="), 10).unwrap_err();
+        assert!(matches!(error, BufferError::LimitExceeded { max: 10 }));
+
+        for invalid in ["aGk", "aGl=", "aGVsbG8gd29ybGQ!"] {
+            let error =
+                Buffer::decode_base64_reader(SmallChunks(invalid.as_bytes()), 100).unwrap_err();
+            assert!(matches!(error, BufferError::Base64(_)), "{invalid}");
+        }
+
+        let mut long = Buffer::new(vec![7; 5000]).to_base64();
+        long.replace_range(3000..3001, "*");
+        let BufferError::Base64(error) =
+            Buffer::decode_base64_reader(long.as_bytes(), 5000).unwrap_err()
+        else {
+            panic!("expected a base64 error");
+        };
+        assert_eq!(error.position(), 3000);
+        assert_eq!(error.0.kind, DecodeKind::Symbol);
+    }
+
+    #[test]
+    fn test_append_base64() {
+        let mut buffer = Buffer::new("hello");
+        buffer.append_base64("IHdvcmxk").unwrap();
+        assert_eq!(buffer, Buffer::new("hello world"));
+
+        buffer.append_base64("").unwrap();
+        assert_eq!(buffer, Buffer::new("hello world"));
+
+        assert_eq!(buffer.append_base64("aGk!").unwrap_err().position(), 3);
+        assert_eq!(buffer.append_base64("IQ").unwrap_err().position(), 0);
+        assert_eq!(buffer, Buffer::new("hello world"));
+
+        buffer.append_base64("IQ==").unwrap();
+        assert_eq!(buffer, Buffer::new("hello world!"));
+    }
+
+    #[test]
+    fn test_base64_accumulator() {
+        let base64 = "aGVsbG8gd29ybGQ=";
+
+        for split in [1, 2, 3, 4, 5, 16] {
+            let mut accumulator = Base64Accumulator::new();
+            for piece in base64.as_bytes().chunks(split) {
+                accumulator = accumulator.push_str(std::str::from_utf8(piece).unwrap());
+            }
+
+            assert_eq!(
+                accumulator.finish().unwrap(),
+                Buffer::new("hello world"),
+                "{split}"
+            );
+        }
+
+        assert_eq!(
+            Base64Accumulator::new().finish().unwrap(),
+            Buffer::default()
+        );
+        assert_eq!(
+            Base64Accumulator::new().push_str("").finish().unwrap(),
+            Buffer::default()
+        );
+    }
+
+    #[test]
+    fn test_base64_accumulator_invalid() {
+        for invalid in ["aGk", "aGl=", "aGk= ", "aGVsbG8gd29ybGQ!"] {
+            let error = Base64Accumulator::new()
+                .push_str(&invalid[..2])
+                .push_str(&invalid[2..])
+                .finish()
+                .unwrap_err();
+            assert_eq!(
+                error,
+                Base64Error(BASE64.decode(invalid.as_bytes()).unwrap_err()),
+                "{invalid}"
+            );
+        }
+
+        let error = Base64Accumulator::new()
+            .push_str("aGVsbG8g")
+            .push_str("d2*y")
+            .push_str("bGQ=")
+            .finish()
+            .unwrap_err();
+        assert_eq!(error.position(), 10);
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        for len in 0..=16 {
+            let bytes = (0..len).collect::<Vec<u8>>();
+            let base64 = BASE64.encode(&bytes);
+
+            let decoded = decode_base64(base64.as_bytes()).unwrap();
+            assert_eq!(decoded, bytes);
+            assert!(decoded.capacity() <= len as usize + 2, "{len}");
+        }
+
+        assert_eq!(
+            decode_base64(b"aGk!"),
+            Err(BASE64.decode(b"aGk!").unwrap_err())
+        );
+    }
+}
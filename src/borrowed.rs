@@ -0,0 +1,117 @@
+//! Deserializes a Roblox buffer that borrows its bytes from the input where possible, for use with
+//! `#[serde(borrow, deserialize_with = "roblox_buffer::borrowed::deserialize")]`.
+//!
+//! Formats that aren't human readable lend out the bytes when they can, such as bincode or MessagePack read from a
+//! slice. The bytes are still copied when the format can only give them temporarily, such as when reading from an
+//! `io::Read`, and always for human readable formats, where the base64 has to be decoded into a new allocation.
+//!
+//! ```
+//! use std::borrow::Cow;
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Save<'a> {
+//!     #[serde(borrow, deserialize_with = "roblox_buffer::borrowed::deserialize")]
+//!     data: roblox_buffer::Buffer<Cow<'a, [u8]>>,
+//! }
+//! ```
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Deserializer, de};
+
+use crate::{Buffer, DEFAULT_ENCODING};
+
+/// Deserializes a Roblox buffer, borrowing its bytes from the input if the format allows it.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Buffer<Cow<'de, [u8]>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct BorrowedVisitor;
+
+    impl<'de> de::Visitor<'de> for BorrowedVisitor {
+        type Value = Cow<'de, [u8]>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("bytes")
+        }
+
+        fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+            Ok(Cow::Borrowed(v))
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok(Cow::Owned(v.to_vec()))
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(Cow::Owned(v))
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            Vec::<u8>::deserialize(de::value::SeqAccessDeserializer::new(seq)).map(Cow::Owned)
+        }
+    }
+
+    if !deserializer.is_human_readable() {
+        return deserializer
+            .deserialize_bytes(BorrowedVisitor)
+            .map(|data| Buffer(data, DEFAULT_ENCODING));
+    }
+
+    crate::de::deserialize_envelope(deserializer, false)
+        .map(|buffer| Buffer(Cow::Owned(buffer.0), buffer.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_borrowed() {
+        let buffer = Buffer::new("hello");
+        let messagepack = rmp_serde::to_vec(&buffer).unwrap();
+
+        let borrowed =
+            deserialize(&mut rmp_serde::Deserializer::from_read_ref(&messagepack)).unwrap();
+        assert_eq!(borrowed, buffer);
+        assert!(matches!(borrowed.0, Cow::Borrowed(_)));
+        assert!(
+            messagepack
+                .as_ptr_range()
+                .contains(&borrowed.as_ref().as_ptr())
+        );
+
+        let copied =
+            deserialize(&mut rmp_serde::Deserializer::new(messagepack.as_slice())).unwrap();
+        assert_eq!(copied, buffer);
+        assert!(matches!(copied.0, Cow::Owned(_)));
+
+        let json = serde_json::to_string(&buffer).unwrap();
+        let decoded = deserialize(&mut serde_json::Deserializer::from_str(&json)).unwrap();
+        assert_eq!(decoded, buffer);
+        assert!(matches!(decoded.0, Cow::Owned(_)));
+    }
+
+    #[test]
+    fn test_borrowed_field() {
+        #[derive(Deserialize)]
+        struct Save<'a> {
+            #[serde(borrow, deserialize_with = "crate::borrowed::deserialize")]
+            data: Buffer<Cow<'a, [u8]>>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct SaveOut {
+            data: Buffer,
+        }
+
+        let messagepack = rmp_serde::to_vec(&SaveOut {
+            data: Buffer::new("hello"),
+        })
+        .unwrap();
+
+        let save: Save = rmp_serde::from_slice(&messagepack).unwrap();
+        assert_eq!(save.data, Buffer::new("hello"));
+        assert!(matches!(save.data.0, Cow::Borrowed(_)));
+    }
+}
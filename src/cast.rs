@@ -0,0 +1,82 @@
+use bytemuck::{Pod, PodCastError};
+
+use crate::Buffer;
+
+/// Returned by [`Buffer::cast_slice`] and [`Buffer::cast_slice_mut`] when the buffer can't be viewed as the type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastError {
+    /// The start of the buffer isn't aligned for the type.
+    Misaligned,
+    /// The length of the buffer isn't a multiple of the size of the type.
+    LengthMismatch,
+}
+
+impl std::fmt::Display for CastError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CastError::Misaligned => write!(f, "buffer isn't aligned for the type"),
+            CastError::LengthMismatch => {
+                write!(f, "buffer length isn't a multiple of the size of the type")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CastError {}
+
+impl From<PodCastError> for CastError {
+    fn from(error: PodCastError) -> Self {
+        match error {
+            PodCastError::TargetAlignmentGreaterAndInputNotAligned
+            | PodCastError::AlignmentMismatch => CastError::Misaligned,
+            PodCastError::OutputSliceWouldHaveSlop | PodCastError::SizeMismatch => {
+                CastError::LengthMismatch
+            }
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Views the buffer as a slice of `P`, without copying. Enabled with the `bytemuck` feature.
+    ///
+    /// The values are in native byte order, which is little endian, the same as Roblox, on almost every platform.
+    /// Allocators usually align buffers enough for any primitive, but this isn't guaranteed, so handle
+    /// [`CastError::Misaligned`] for buffers that don't start at the beginning of an allocation, such as views.
+    pub fn cast_slice<P: Pod>(&self) -> Result<&[P], CastError> {
+        Ok(bytemuck::try_cast_slice(self.as_ref())?)
+    }
+}
+
+impl Buffer {
+    /// Views the buffer as a mutable slice of `P`, for editing values in place. Enabled with the `bytemuck` feature.
+    /// Has the same checks and byte order caveat as [`Buffer::cast_slice`].
+    pub fn cast_slice_mut<P: Pod>(&mut self) -> Result<&mut [P], CastError> {
+        Ok(bytemuck::try_cast_slice_mut(&mut self.0)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cast_slice() {
+        let mut buffer = Buffer::new(vec![1, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(buffer.cast_slice::<u32>(), Ok(&[1, 2][..]));
+
+        for value in buffer.cast_slice_mut::<u32>().unwrap() {
+            *value *= 0x0101;
+        }
+        assert_eq!(buffer, Buffer::new(vec![1, 1, 0, 0, 2, 2, 0, 0]));
+
+        assert_eq!(buffer.view(..7).cast_slice::<u8>().map(<[u8]>::len), Ok(7));
+        assert_eq!(
+            buffer.view(..6).cast_slice::<u32>(),
+            Err(CastError::LengthMismatch)
+        );
+        assert_eq!(
+            buffer.view(1..5).cast_slice::<u32>(),
+            Err(CastError::Misaligned)
+        );
+    }
+}
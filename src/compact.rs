@@ -0,0 +1,146 @@
+use serde::Serialize;
+
+use crate::{Buffer, EncodeOptions};
+
+/// A buffer kept as either its raw bytes or a zstd frame, whichever is smaller, created with [`Buffer::compact`].
+/// Serializes straight from the stored form, without compressing again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactBuffer(Stored);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Stored {
+    Raw(Vec<u8>),
+    Zstd { frame: Vec<u8>, len: usize },
+}
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Compresses the buffer for keeping in memory, if that makes it smaller.
+    pub fn compact(&self) -> CompactBuffer {
+        let data = self.0.as_ref();
+
+        let frame = crate::encode::compress_into(data, &EncodeOptions::default(), Vec::new())
+            .ok()
+            .filter(|frame| frame.len() < data.len());
+
+        CompactBuffer(match frame {
+            Some(frame) => Stored::Zstd {
+                frame,
+                len: data.len(),
+            },
+            None => Stored::Raw(data.to_vec()),
+        })
+    }
+}
+
+impl CompactBuffer {
+    /// Returns whether the buffer is stored compressed.
+    pub fn is_compressed(&self) -> bool {
+        matches!(self.0, Stored::Zstd { .. })
+    }
+
+    /// Returns the number of bytes the buffer takes up as stored.
+    pub fn stored_len(&self) -> usize {
+        match &self.0 {
+            Stored::Raw(data) => data.len(),
+            Stored::Zstd { frame, .. } => frame.len(),
+        }
+    }
+
+    /// Returns the length of the buffer once decompressed.
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Stored::Raw(data) => data.len(),
+            Stored::Zstd { len, .. } => *len,
+        }
+    }
+
+    /// Returns whether the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the buffer, decompressing it if needed.
+    pub fn decompress(&self) -> Buffer {
+        match &self.0 {
+            Stored::Raw(data) => Buffer::new(data.clone()),
+            Stored::Zstd { frame, len } => Buffer::new(
+                zstd::bulk::decompress(frame, *len)
+                    .expect("compact buffers hold a valid zstd frame"),
+            ),
+        }
+    }
+}
+
+impl Serialize for CompactBuffer {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        match &self.0 {
+            Stored::Raw(data) if !serializer.is_human_readable() => {
+                serializer.serialize_bytes(data)
+            }
+            Stored::Raw(data) => crate::encode::serialize_precompressed("base64", data, serializer),
+            Stored::Zstd { .. } if !serializer.is_human_readable() => {
+                self.decompress().serialize(serializer)
+            }
+            Stored::Zstd { frame, .. } => {
+                crate::encode::serialize_precompressed("zbase64", frame, serializer)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BufferEncoding;
+
+    use super::*;
+
+    #[test]
+    fn test_compressible() {
+        let buffer = Buffer::new(vec![7; 10_000]);
+        let compact = buffer.compact();
+
+        assert!(compact.is_compressed());
+        assert!(compact.stored_len() < 100);
+        assert_eq!(compact.len(), 10_000);
+        assert_eq!(compact.decompress(), buffer);
+
+        assert_eq!(
+            serde_json::to_string(&compact).unwrap(),
+            serde_json::to_string(&buffer.clone().with_encoding(BufferEncoding::ZBase64)).unwrap()
+        );
+        assert_eq!(
+            serde_json::from_str::<Buffer>(&serde_json::to_string(&compact).unwrap()).unwrap(),
+            buffer
+        );
+    }
+
+    #[test]
+    fn test_incompressible() {
+        let buffer = Buffer::new("hello");
+        let compact = buffer.compact();
+
+        assert!(!compact.is_compressed());
+        assert_eq!(compact.stored_len(), 5);
+        assert_eq!(compact.decompress(), buffer);
+        assert_eq!(
+            serde_json::to_string(&compact).unwrap(),
+            r#"{"m":null,"t":"buffer","base64":"aGVsbG8="}"#
+        );
+
+        assert!(Buffer::default().compact().is_empty());
+    }
+
+    #[test]
+    fn test_not_human_readable() {
+        for buffer in [Buffer::new(vec![7; 10_000]), Buffer::new("hello")] {
+            let compact = buffer.compact();
+            let packed = rmp_serde::to_vec(&compact).unwrap();
+
+            assert_eq!(packed, rmp_serde::to_vec(&buffer).unwrap());
+            assert_eq!(rmp_serde::from_slice::<Buffer>(&packed).unwrap(), buffer);
+        }
+    }
+}
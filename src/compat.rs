@@ -0,0 +1,296 @@
+//! Checks that buffer JSON will import into Roblox, enabled with the `json` feature.
+
+use crate::{Base64Error, Buffer, WINDOW_LOG_RANGE};
+
+/// The keys that can hold the data of a buffer, in the order they're checked.
+const DATA_KEYS: [&str; 4] = ["base64", "zbase64", "data", "gzbase64"];
+
+/// A reason buffer JSON might not import into Roblox, returned by [`Buffer::validate_roblox_json`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompatibilityIssue {
+    /// The input wasn't a JSON object.
+    NotAnObject,
+    /// `t` was missing or wasn't `buffer`.
+    WrongTag,
+    /// There was no data key.
+    MissingData,
+    /// There was more than one data key, such as `base64` alongside `zbase64`.
+    MultipleDataKeys(Vec<&'static str>),
+    /// The data was under a key this crate reads but Roblox doesn't, such as `data` or `gzbase64`.
+    UnsupportedDataKey(&'static str),
+    /// The data wasn't a string.
+    NotAString(&'static str),
+    /// The data wasn't padded base64 in the standard alphabet.
+    InvalidBase64 {
+        /// The key the data was under.
+        key: &'static str,
+        /// Why it failed to decode.
+        error: Base64Error,
+    },
+    /// `zbase64` wasn't made up of valid zstd frames.
+    InvalidFrame,
+    /// A zstd frame needs a dictionary to decompress.
+    DictionaryRequired,
+    /// A zstd frame needs a larger window to decompress than Roblox accepts.
+    WindowTooLarge {
+        /// The window the frame needs, in bytes.
+        window_len: u64,
+        /// The largest window accepted, `2^27` bytes.
+        max: u64,
+    },
+}
+
+impl std::fmt::Display for CompatibilityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompatibilityIssue::NotAnObject => f.write_str("expected a JSON object"),
+            CompatibilityIssue::WrongTag => f.write_str("expected `t` to be `buffer`"),
+            CompatibilityIssue::MissingData => f.write_str("expected base64 or zbase64"),
+            CompatibilityIssue::MultipleDataKeys(keys) => {
+                write!(
+                    f,
+                    "expected only one data key, but found {}",
+                    keys.join(", ")
+                )
+            }
+            CompatibilityIssue::UnsupportedDataKey(key) => {
+                write!(f, "`{key}` isn't supported by Roblox")
+            }
+            CompatibilityIssue::NotAString(key) => write!(f, "expected `{key}` to be a string"),
+            CompatibilityIssue::InvalidBase64 { key, error } => write!(f, "`{key}` has {error}"),
+            CompatibilityIssue::InvalidFrame => f.write_str("zbase64 isn't a valid zstd frame"),
+            CompatibilityIssue::DictionaryRequired => {
+                f.write_str("zstd frame requires a dictionary")
+            }
+            CompatibilityIssue::WindowTooLarge { window_len, max } => write!(
+                f,
+                "zstd frame needs a window of {window_len} bytes, which is over the limit of {max} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CompatibilityIssue {}
+
+impl Buffer {
+    /// Checks that buffer JSON will import into Roblox, reporting every problem found.
+    ///
+    /// This is stricter than deserializing a [`Buffer`], which accepts forms that Roblox doesn't, such as
+    /// `base64` alongside `zbase64`.
+    pub fn validate_roblox_json(s: &str) -> Result<(), Vec<CompatibilityIssue>> {
+        let Ok(serde_json::Value::Object(object)) = serde_json::from_str(s) else {
+            return Err(vec![CompatibilityIssue::NotAnObject]);
+        };
+
+        let mut issues = Vec::new();
+
+        if object.get("t").and_then(serde_json::Value::as_str) != Some("buffer") {
+            issues.push(CompatibilityIssue::WrongTag);
+        }
+
+        let keys: Vec<_> = DATA_KEYS
+            .into_iter()
+            .filter(|&key| object.contains_key(key))
+            .collect();
+
+        match keys.len() {
+            0 => issues.push(CompatibilityIssue::MissingData),
+            1 => {}
+            _ => issues.push(CompatibilityIssue::MultipleDataKeys(keys.clone())),
+        }
+
+        for key in keys {
+            if key != "base64" && key != "zbase64" {
+                issues.push(CompatibilityIssue::UnsupportedDataKey(key));
+                continue;
+            }
+
+            let Some(base64) = object[key].as_str() else {
+                issues.push(CompatibilityIssue::NotAString(key));
+                continue;
+            };
+
+            match crate::base64::decode_base64(base64.as_bytes()) {
+                Ok(frames) if key == "zbase64" => check_frames(&frames, &mut issues),
+                Ok(_) => {}
+                Err(error) => issues.push(CompatibilityIssue::InvalidBase64 {
+                    key,
+                    error: error.into(),
+                }),
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+}
+
+/// Checks the header of each zstd frame, one after another.
+fn check_frames(mut frames: &[u8], issues: &mut Vec<CompatibilityIssue>) {
+    let max = 1 << WINDOW_LOG_RANGE.end();
+
+    if frames.is_empty() {
+        issues.push(CompatibilityIssue::InvalidFrame);
+    }
+
+    while !frames.is_empty() {
+        let Some(&descriptor) = frames
+            .get(4)
+            .filter(|_| frames.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]))
+        else {
+            issues.push(CompatibilityIssue::InvalidFrame);
+            return;
+        };
+
+        // The reserved bit must be unset.
+        if descriptor & (1 << 3) != 0 {
+            issues.push(CompatibilityIssue::InvalidFrame);
+            return;
+        }
+
+        let single_segment = descriptor & (1 << 5) != 0;
+        let dictionary_start = if single_segment { 5 } else { 6 };
+        let dictionary_len = [0, 1, 2, 4][(descriptor & 0b11) as usize];
+        let content_size_start = dictionary_start + dictionary_len;
+        let content_size_len = match descriptor >> 6 {
+            0 if single_segment => 1,
+            0 => 0,
+            1 => 2,
+            2 => 4,
+            _ => 8,
+        };
+
+        let Some(header) = frames.get(..content_size_start + content_size_len) else {
+            issues.push(CompatibilityIssue::InvalidFrame);
+            return;
+        };
+
+        // A dictionary ID of 0 means there is no dictionary.
+        if header[dictionary_start..content_size_start]
+            .iter()
+            .any(|&byte| byte != 0)
+        {
+            issues.push(CompatibilityIssue::DictionaryRequired);
+        }
+
+        // Single segment frames need a window as large as their content.
+        let window_len = if single_segment {
+            let content_size = header[content_size_start..]
+                .iter()
+                .rev()
+                .fold(0, |size, &byte| size << 8 | u64::from(byte));
+
+            if content_size_len == 2 {
+                content_size + 256
+            } else {
+                content_size
+            }
+        } else {
+            let window_log = 10 + u32::from(header[5] >> 3);
+            let base = 1u64 << window_log;
+            base + base / 8 * u64::from(header[5] & 0b111)
+        };
+
+        if window_len > max {
+            issues.push(CompatibilityIssue::WindowTooLarge { window_len, max });
+        }
+
+        match zstd::zstd_safe::find_frame_compressed_size(frames) {
+            Ok(len) => frames = &frames[len..],
+            Err(_) => {
+                issues.push(CompatibilityIssue::InvalidFrame);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use data_encoding::BASE64;
+
+    use super::*;
+    use crate::BufferEncoding;
+
+    #[test]
+    fn test_validate_roblox_json() {
+        // Exported from Roblox.
+        assert_eq!(
+            Buffer::validate_roblox_json(
+                r#"{"m":null,"t":"buffer","zbase64":"KLUv/SBfbQAAMGhlbGxvIAEAlqkUAQ=="}"#
+            ),
+            Ok(())
+        );
+
+        let hello = Buffer::new("hello ".repeat(100));
+        for encoding in [BufferEncoding::Base64, BufferEncoding::ZBase64] {
+            let json = serde_json::to_string(&hello.clone().with_encoding(encoding)).unwrap();
+            assert_eq!(Buffer::validate_roblox_json(&json), Ok(()), "{json}");
+        }
+
+        assert_eq!(
+            Buffer::validate_roblox_json(r#"{"t":"buf","base64":"aGk=","zbase64":"a-b_"}"#),
+            Err(vec![
+                CompatibilityIssue::WrongTag,
+                CompatibilityIssue::MultipleDataKeys(vec!["base64", "zbase64"]),
+                CompatibilityIssue::InvalidBase64 {
+                    key: "zbase64",
+                    error: BASE64.decode(b"a-b_").unwrap_err().into(),
+                },
+            ])
+        );
+        assert_eq!(
+            Buffer::validate_roblox_json(r#"{"t":"buffer","zbase64":"aGk="}"#),
+            Err(vec![CompatibilityIssue::InvalidFrame])
+        );
+        assert_eq!(
+            Buffer::validate_roblox_json(r#"{"t":"buffer","data":"aGk=","compression":"none"}"#),
+            Err(vec![CompatibilityIssue::UnsupportedDataKey("data")])
+        );
+        assert_eq!(
+            Buffer::validate_roblox_json(r#"{"t":"buffer","base64":[104, 105]}"#),
+            Err(vec![CompatibilityIssue::NotAString("base64")])
+        );
+        assert_eq!(
+            Buffer::validate_roblox_json(r#"{"m":null}"#),
+            Err(vec![
+                CompatibilityIssue::WrongTag,
+                CompatibilityIssue::MissingData
+            ])
+        );
+        assert_eq!(
+            Buffer::validate_roblox_json("[]"),
+            Err(vec![CompatibilityIssue::NotAnObject])
+        );
+    }
+
+    #[test]
+    fn test_validate_roblox_json_window_too_large() {
+        // Not single segment, with a window log of 28, then an empty raw block.
+        let frame = [0x28, 0xb5, 0x2f, 0xfd, 0x00, 18 << 3, 0x01, 0x00, 0x00];
+        let json = format!(
+            r#"{{"m":null,"t":"buffer","zbase64":"{}"}}"#,
+            BASE64.encode(&frame)
+        );
+
+        assert_eq!(
+            Buffer::validate_roblox_json(&json),
+            Err(vec![CompatibilityIssue::WindowTooLarge {
+                window_len: 1 << 28,
+                max: 1 << 27,
+            }])
+        );
+
+        // The largest window that's accepted.
+        let frame = [0x28, 0xb5, 0x2f, 0xfd, 0x00, 17 << 3, 0x01, 0x00, 0x00];
+        let json = format!(
+            r#"{{"m":null,"t":"buffer","zbase64":"{}"}}"#,
+            BASE64.encode(&frame)
+        );
+        assert_eq!(Buffer::validate_roblox_json(&json), Ok(()));
+    }
+}
@@ -0,0 +1,461 @@
+//! Decodes the Roblox buffer envelope, shared by the [`Deserialize`] implementation and the permissive adapters.
+
+use std::{borrow::Cow, io};
+
+use data_encoding::DecodeError;
+use serde::{Deserialize, Deserializer, de};
+
+use crate::{
+    Buffer, BufferEncoding, BufferError, DEFAULT_ENCODING, MAX_BUFFER_LEN, PolicyViolation,
+    decompress,
+};
+
+/// The data of a buffer as written, before decoding.
+enum Payload<'a> {
+    // Borrowed where possible, to avoid copying the base64 before decoding it.
+    Base64(Cow<'a, str>),
+    // Only accepted by permissive decoding, from exporters that write the bytes as an array of numbers.
+    Bytes(Vec<u8>),
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Payload<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PayloadVisitor;
+
+        impl<'de> de::Visitor<'de> for PayloadVisitor {
+            type Value = Payload<'de>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a base64 string")
+            }
+
+            fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+                Ok(Payload::Base64(Cow::Borrowed(v)))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(Payload::Base64(Cow::Owned(v.to_owned())))
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+                Ok(Payload::Base64(Cow::Owned(v)))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                Vec::<u8>::deserialize(de::value::SeqAccessDeserializer::new(seq))
+                    .map(Payload::Bytes)
+            }
+        }
+
+        deserializer.deserialize_any(PayloadVisitor)
+    }
+}
+
+enum BufferData<'a> {
+    Base64(Payload<'a>),
+    ZBase64(Payload<'a>),
+    #[cfg(feature = "gzip")]
+    GzBase64(Payload<'a>),
+    // Compressed according to the `compression` field.
+    Data(Payload<'a>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Compression {
+    None,
+    Zstd,
+    // Only from the `gzbase64` key, which other tools write. Roblox itself only uses zstd.
+    #[cfg(feature = "gzip")]
+    #[serde(skip)]
+    Gzip,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum Key {
+    T,
+    Compression,
+    Len,
+    Base64,
+    ZBase64,
+    #[cfg(feature = "gzip")]
+    GzBase64,
+    Data,
+    // Includes `m`, which is always null.
+    #[serde(other)]
+    Other,
+}
+
+struct BufferInner<'a> {
+    t: Option<Cow<'a, str>>,
+    compression: Option<Compression>,
+    len: Option<usize>,
+    data: BufferData<'a>,
+}
+
+// Written by hand rather than flattening `BufferData` in, so that the map is read directly instead of being
+// buffered first, which also lets buffers be flattened into other structs.
+impl<'de: 'a, 'a> Deserialize<'de> for BufferInner<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BufferInnerVisitor;
+
+        impl<'de> de::Visitor<'de> for BufferInnerVisitor {
+            type Value = BufferInner<'de>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a buffer")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut t = None;
+                let mut compression = None;
+                let mut len = None;
+                let mut data = None;
+
+                while let Some(key) = map.next_key()? {
+                    match key {
+                        Key::T if t.is_some() => return Err(de::Error::duplicate_field("t")),
+                        Key::T => t = Some(map.next_value::<Cow<'de, str>>()?),
+                        Key::Compression if compression.is_some() => {
+                            return Err(de::Error::duplicate_field("compression"));
+                        }
+                        Key::Compression => compression = Some(map.next_value()?),
+                        Key::Len if len.is_some() => return Err(de::Error::duplicate_field("len")),
+                        Key::Len => len = Some(map.next_value()?),
+                        // The first data key is used, the same as earlier versions of this crate, which wrote
+                        // `base64` alongside `zbase64`.
+                        Key::Other | Key::Base64 | Key::ZBase64 | Key::Data if data.is_some() => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                        #[cfg(feature = "gzip")]
+                        Key::GzBase64 if data.is_some() => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                        Key::Base64 => data = Some(BufferData::Base64(map.next_value()?)),
+                        Key::ZBase64 => data = Some(BufferData::ZBase64(map.next_value()?)),
+                        #[cfg(feature = "gzip")]
+                        Key::GzBase64 => data = Some(BufferData::GzBase64(map.next_value()?)),
+                        Key::Data => data = Some(BufferData::Data(map.next_value()?)),
+                        Key::Other => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                Ok(BufferInner {
+                    t,
+                    compression: compression.flatten(),
+                    len: len.flatten(),
+                    data: data
+                        .ok_or_else(|| de::Error::custom("expected base64, zbase64, or data"))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(BufferInnerVisitor)
+    }
+}
+
+/// Splits base64 at the first character that can't be part of it, returning the base64 and what came after it.
+pub(crate) fn split_trailer(base64: &str) -> (&str, Option<&str>) {
+    match base64.find(|char: char| !(char.is_ascii_alphanumeric() || "+/=".contains(char))) {
+        Some(index) => (&base64[..index], Some(&base64[index..])),
+        None => (base64, None),
+    }
+}
+
+/// Deserializes a buffer from its envelope.
+pub(crate) fn deserialize_envelope<'de, D>(
+    deserializer: D,
+    permissive: bool,
+) -> Result<Buffer, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_envelope_with_trailer(deserializer, permissive).map(|(buffer, _)| buffer)
+}
+
+/// Deserializes a buffer from its envelope, along with anything after the base64 when `permissive`.
+///
+/// When `permissive`, the data can also be an array of bytes, which is used as is instead of being base64 decoded.
+/// Base64 can also be followed by anything starting with a character that isn't base64, such as a `#checksum`
+/// comment, which is returned instead of failing to decode.
+pub(crate) fn deserialize_envelope_with_trailer<'de, D>(
+    deserializer: D,
+    permissive: bool,
+) -> Result<(Buffer, Option<String>), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let inner = BufferInner::deserialize(deserializer)?;
+    decode_envelope(inner, permissive, &Limits::DEFAULT)
+        .map(|decoded| (decoded.buffer, decoded.trailer))
+        .map_err(Failure::into_de_error)
+}
+
+/// Reads a buffer from JSON with limits on what it can decode to, for [`DecodeOptions`](crate::DecodeOptions).
+/// Also returns the encoding the buffer was stored with.
+#[cfg(feature = "json")]
+pub(crate) fn decode_json(
+    s: &str,
+    limits: &Limits,
+) -> Result<(Buffer, BufferEncoding), BufferError> {
+    let inner: BufferInner = serde_json::from_str(s)?;
+    decode_envelope(inner, false, limits)
+        .map(|decoded| (decoded.buffer, decoded.encoding))
+        .map_err(|failure| failure.into_buffer_error(limits))
+}
+
+/// A buffer envelope read from JSON, with its data still encoded, for decoding the data as a stream.
+#[cfg(feature = "json")]
+pub(crate) struct JsonEnvelope<'a> {
+    pub(crate) base64: Cow<'a, str>,
+    pub(crate) compression: Compression,
+    pub(crate) len: Option<usize>,
+}
+
+/// Reads a buffer envelope from JSON, checking it the same way as deserializing a [`Buffer`] does, without
+/// decoding the data.
+#[cfg(feature = "json")]
+pub(crate) fn open_json(s: &str) -> Result<JsonEnvelope<'_>, BufferError> {
+    let inner: BufferInner = serde_json::from_str(s)?;
+    let limits = &Limits::DEFAULT;
+
+    match open_envelope(inner, limits).map_err(|failure| failure.into_buffer_error(limits))? {
+        (Payload::Base64(base64), compression, len) => Ok(JsonEnvelope {
+            base64,
+            compression,
+            len,
+        }),
+        (Payload::Bytes(_), ..) => Err(Failure::Invalid(
+            "expected a base64 string, but found an array of bytes",
+        )
+        .into_buffer_error(limits)),
+    }
+}
+
+/// Limits on decoding a buffer.
+pub(crate) struct Limits {
+    /// The most bytes the buffer can decode to.
+    pub(crate) max_decoded: usize,
+    /// Whether the buffer can be compressed.
+    pub(crate) allow_compressed: bool,
+    /// Whether `t` has to be `buffer`.
+    pub(crate) strict_tag: bool,
+    /// Whether breaking these limits is reported as [`BufferError::PolicyViolation`], rather than as invalid JSON.
+    #[cfg(feature = "json")]
+    pub(crate) report_policy: bool,
+}
+
+impl Limits {
+    /// The limits when deserializing a [`Buffer`].
+    pub(crate) const DEFAULT: Limits = Limits {
+        max_decoded: MAX_BUFFER_LEN,
+        allow_compressed: true,
+        strict_tag: true,
+        #[cfg(feature = "json")]
+        report_policy: false,
+    };
+}
+
+/// Why an envelope failed to decode, kept apart from the error type so that it can be reported as either a serde
+/// error or a [`BufferError`].
+enum Failure {
+    /// The envelope wasn't valid, reported as a serde error either way.
+    Invalid(&'static str),
+    Policy(PolicyViolation),
+    Base64(DecodeError),
+    Io(io::Error),
+    LengthMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    LimitExceeded {
+        max: usize,
+    },
+}
+
+impl Failure {
+    fn into_de_error<E: de::Error>(self) -> E {
+        match self {
+            Failure::Invalid(message) => E::custom(message),
+            Failure::Policy(PolicyViolation::MissingTag) => E::missing_field("t"),
+            Failure::Policy(violation) => E::custom(violation),
+            Failure::Base64(error) => E::custom(error),
+            Failure::Io(error) => E::custom(error),
+            Failure::LengthMismatch { expected, actual } => {
+                E::custom(BufferError::LengthMismatch { expected, actual })
+            }
+            Failure::LimitExceeded { max } => E::custom(BufferError::LimitExceeded { max }),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    fn into_buffer_error(self, limits: &Limits) -> BufferError {
+        match self {
+            Failure::Invalid(message) => BufferError::Json(de::Error::custom(message)),
+            Failure::Policy(violation) if limits.report_policy => {
+                BufferError::PolicyViolation(violation)
+            }
+            Failure::Policy(violation) => BufferError::Json(de::Error::custom(violation)),
+            Failure::Base64(error) => error.into(),
+            Failure::Io(error) => match error.get_ref().and_then(|inner| inner.downcast_ref()) {
+                Some(&BufferError::LimitExceeded { max }) => BufferError::LimitExceeded { max },
+                _ => error.into(),
+            },
+            Failure::LengthMismatch { expected, actual } => {
+                BufferError::LengthMismatch { expected, actual }
+            }
+            Failure::LimitExceeded { max } => BufferError::LimitExceeded { max },
+        }
+    }
+}
+
+/// A buffer decoded from its envelope.
+struct Decoded {
+    buffer: Buffer,
+    /// Anything after the base64, when permissive.
+    trailer: Option<String>,
+    /// The encoding the buffer was stored with.
+    #[cfg_attr(not(feature = "json"), expect(dead_code))]
+    encoding: BufferEncoding,
+}
+
+/// Checks the envelope against the limits, returning its data, how the data is compressed, and its expected length.
+fn open_envelope<'a>(
+    inner: BufferInner<'a>,
+    limits: &Limits,
+) -> Result<(Payload<'a>, Compression, Option<usize>), Failure> {
+    let BufferInner {
+        t,
+        compression,
+        len,
+        data,
+    } = inner;
+
+    if limits.strict_tag {
+        match t {
+            Some(t) if t == "buffer" => {}
+            Some(_) => return Err(Failure::Policy(PolicyViolation::WrongTag)),
+            None => return Err(Failure::Policy(PolicyViolation::MissingTag)),
+        }
+    }
+
+    let (payload, compression) = match (data, compression) {
+        (BufferData::Base64(base64), None) => (base64, Compression::None),
+        (BufferData::ZBase64(zbase64), None) => (zbase64, Compression::Zstd),
+        #[cfg(feature = "gzip")]
+        (BufferData::GzBase64(gzbase64), None) => (gzbase64, Compression::Gzip),
+        (BufferData::Data(data), compression) => (data, compression.unwrap_or(Compression::None)),
+        (_, Some(_)) => {
+            return Err(Failure::Invalid("compression can only be used with data"));
+        }
+    };
+
+    if !limits.allow_compressed && !matches!(compression, Compression::None) {
+        return Err(Failure::Policy(PolicyViolation::Compressed));
+    }
+
+    Ok((payload, compression, len))
+}
+
+fn decode_envelope(
+    inner: BufferInner<'_>,
+    permissive: bool,
+    limits: &Limits,
+) -> Result<Decoded, Failure> {
+    let (payload, compression, len) = open_envelope(inner, limits)?;
+
+    let max = limits.max_decoded;
+    let mut trailer = None;
+
+    let data = match payload {
+        Payload::Base64(base64) => {
+            let base64 = if permissive {
+                let (base64, rest) = split_trailer(&base64);
+                trailer = rest.map(str::to_owned);
+                base64
+            } else {
+                &base64
+            };
+
+            // Checked before decoding when it's certain to be over. Padding can make it up to 2 bytes less.
+            if matches!(compression, Compression::None)
+                && base64.len() / 4 * 3 > max.saturating_add(2)
+            {
+                return Err(Failure::LimitExceeded { max });
+            }
+
+            let data = crate::base64::decode_base64(base64.as_bytes()).map_err(Failure::Base64)?;
+
+            debug_event!(
+                encoded_len = base64.len(),
+                decoded_len = data.len(),
+                compressed = !matches!(compression, Compression::None),
+                "decoded base64"
+            );
+
+            data
+        }
+
+        Payload::Bytes(bytes) if permissive => bytes,
+        Payload::Bytes(_) => {
+            return Err(Failure::Invalid(
+                "expected a base64 string, but found an array of bytes",
+            ));
+        }
+    };
+
+    // gzip is reported as base64, since there is no encoding for writing it.
+    let encoding = match compression {
+        Compression::Zstd => BufferEncoding::ZBase64,
+        _ => BufferEncoding::Base64,
+    };
+
+    let data = match compression {
+        Compression::None => data,
+        Compression::Zstd => {
+            let decompressed = decompress(&data, max).map_err(Failure::Io)?;
+
+            debug_event!(
+                compressed_len = data.len(),
+                decompressed_len = decompressed.len(),
+                ratio = decompressed.len() as f64 / data.len() as f64,
+                "decompressed buffer"
+            );
+
+            decompressed
+        }
+
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => decompress_gzip(&data, max).map_err(Failure::Io)?,
+    };
+
+    if data.len() > max {
+        return Err(Failure::LimitExceeded { max });
+    }
+
+    if let Some(len) = len
+        && len != data.len()
+    {
+        return Err(Failure::LengthMismatch {
+            expected: len,
+            actual: data.len(),
+        });
+    }
+
+    Ok(Decoded {
+        buffer: Buffer(data, DEFAULT_ENCODING),
+        trailer,
+        encoding,
+    })
+}
+
+/// Decompresses gzip members, one after another, failing with [`BufferError::LimitExceeded`] inside the
+/// [`io::Error`] instead of decompressing more than `max` bytes.
+/// Allocation failures are returned as errors instead of aborting, the same as for zstd.
+#[cfg(feature = "gzip")]
+fn decompress_gzip(compressed: &[u8], max: usize) -> io::Result<Vec<u8>> {
+    crate::read_to_end_limited(flate2::read::MultiGzDecoder::new(compressed), 0, max)
+}
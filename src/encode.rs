@@ -0,0 +1,456 @@
+//! Streams buffers into serializers, so that serializing doesn't need to hold the encoded data in memory.
+
+use std::io::{self, Write};
+
+use data_encoding::BASE64;
+use serde::Serialize;
+
+use crate::{BufferEncoding, EncodeOptions};
+
+/// Serializes bytes as a Roblox buffer using the given options.
+/// Formats that aren't human readable get the raw bytes instead, since they can store them directly.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "serialize_buffer",
+        level = "debug",
+        skip_all,
+        fields(len = data.len(), encoding = ?options.encoding)
+    )
+)]
+pub(crate) fn serialize_bytes<S>(
+    data: &[u8],
+    options: &EncodeOptions,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::ser::Serializer,
+{
+    if !serializer.is_human_readable() {
+        return serializer.serialize_bytes(data);
+    }
+
+    // Only `Auto` needs to know how large the compressed data is before choosing, which means compressing twice.
+    let key = match options.encoding {
+        BufferEncoding::Base64 => "base64",
+        BufferEncoding::ZBase64 => "zbase64",
+        BufferEncoding::Auto => {
+            encoded_len(data, options)
+                .map_err(serde::ser::Error::custom)?
+                .0
+        }
+    };
+
+    debug_event!(key, "chose encoding");
+
+    serialize_envelope(
+        key,
+        &Encoded {
+            data,
+            compress: (key == "zbase64").then_some(options),
+        },
+        options.include_len.then_some(data.len()),
+        serializer,
+    )
+}
+
+/// Serializes bytes that were already compressed by the caller as base64 under the given key.
+pub(crate) fn serialize_precompressed<S>(
+    key: &str,
+    compressed: &[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::ser::Serializer,
+{
+    serialize_envelope(
+        key,
+        &Encoded {
+            data: compressed,
+            compress: None,
+        },
+        None,
+        serializer,
+    )
+}
+
+fn serialize_envelope<S>(
+    key: &str,
+    encoded: &Encoded<'_>,
+    len: Option<usize>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::ser::Serializer,
+{
+    use serde::ser::SerializeMap;
+
+    let mut map = serializer.serialize_map(Some(3 + len.is_some() as usize))?;
+
+    map.serialize_entry("m", &())?; // "m": null
+    map.serialize_entry("t", "buffer")?;
+    if let Some(len) = len {
+        map.serialize_entry("len", &len)?;
+    }
+    map.serialize_entry(key, encoded)?;
+
+    map.end()
+}
+
+/// Returns the key and length of the data that bytes serialize to with the given options.
+/// Compressed data is counted rather than kept, so this uses the same bounded memory as serializing.
+pub(crate) fn encoded_len(
+    data: &[u8],
+    options: &EncodeOptions,
+) -> io::Result<(&'static str, usize)> {
+    let base64_len = BASE64.encode_len(data.len());
+
+    let zbase64_len = || -> io::Result<usize> {
+        let counter = compress_into(data, options, Counter(0))?;
+
+        debug_event!(
+            len = data.len(),
+            compressed_len = counter.0,
+            ratio = data.len() as f64 / counter.0 as f64,
+            "compressed buffer"
+        );
+
+        Ok(BASE64.encode_len(counter.0))
+    };
+
+    match options.encoding {
+        BufferEncoding::Base64 => Ok(("base64", base64_len)),
+        BufferEncoding::ZBase64 => Ok(("zbase64", zbase64_len()?)),
+        BufferEncoding::Auto => {
+            let zbase64_len = zbase64_len()?;
+
+            if zbase64_len < base64_len {
+                Ok(("zbase64", zbase64_len))
+            } else {
+                Ok(("base64", base64_len))
+            }
+        }
+    }
+}
+
+/// Compresses bytes into a single zstd frame the same way Roblox does, writing it to `writer` as it goes.
+///
+/// Roblox needs no framing beyond a standard zstd frame. Its own frames are single segment, include the
+/// content size, and have no checksum or dictionary, which is exactly what the settings below produce.
+/// Earlier versions of this crate failed to import in Roblox because they wrote a `base64` key alongside
+/// `zbase64`, not because of the frame.
+pub(crate) fn compress_into<W: Write>(
+    data: &[u8],
+    options: &EncodeOptions,
+    writer: W,
+) -> io::Result<W> {
+    let mut encoder = zstd::stream::Encoder::new(writer, 0)?;
+    encoder.set_pledged_src_size(Some(data.len() as u64))?;
+    encoder.include_contentsize(true)?;
+    encoder.long_distance_matching(options.long_distance_matching)?;
+    if let Some(window_log) = options.window_log {
+        encoder.window_log(window_log)?;
+    }
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// The base64 of some bytes, optionally compressed first.
+/// Serializes through [`serde::Serializer::collect_str`], which serializers such as `serde_json` write out without
+/// collecting into a string.
+struct Encoded<'a> {
+    data: &'a [u8],
+    compress: Option<&'a EncodeOptions>,
+}
+
+impl std::fmt::Display for Encoded<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut writer = Base64Writer::new(f);
+
+        match self.compress {
+            Some(options) => {
+                writer = compress_into(self.data, options, writer).map_err(|_| std::fmt::Error)?;
+
+                debug_event!(
+                    len = self.data.len(),
+                    compressed_len = writer.len,
+                    ratio = self.data.len() as f64 / writer.len as f64,
+                    "compressed buffer"
+                );
+            }
+
+            None => writer.write_all(self.data).map_err(|_| std::fmt::Error)?,
+        }
+
+        writer.finish()
+    }
+}
+
+impl Serialize for Encoded<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// Base64 encodes everything written to it into a formatter.
+struct Base64Writer<W> {
+    inner: W,
+    // Bytes that don't make up a full base64 block yet.
+    pending: [u8; 3],
+    pending_len: usize,
+    // How many bytes have been written, for logging.
+    #[cfg(feature = "tracing")]
+    len: usize,
+}
+
+impl<W: std::fmt::Write> Base64Writer<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pending: [0; 3],
+            pending_len: 0,
+            #[cfg(feature = "tracing")]
+            len: 0,
+        }
+    }
+
+    fn write_blocks(&mut self, input: &[u8]) -> std::fmt::Result {
+        const CHUNK_LEN: usize = 3 * 256;
+
+        let mut output = [0; CHUNK_LEN / 3 * 4];
+
+        for chunk in input.chunks(CHUNK_LEN) {
+            let output = &mut output[..BASE64.encode_len(chunk.len())];
+            BASE64.encode_mut(chunk, output);
+            self.inner
+                .write_str(std::str::from_utf8(output).expect("base64 is ascii"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes out the last partial block, with padding.
+    fn finish(mut self) -> std::fmt::Result {
+        let pending = self.pending;
+        self.write_blocks(&pending[..self.pending_len])
+    }
+}
+
+impl<W: std::fmt::Write> Write for Base64Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        #[cfg(feature = "tracing")]
+        {
+            self.len += buf.len();
+        }
+
+        let mut input = buf;
+
+        if self.pending_len > 0 {
+            let take = (3 - self.pending_len).min(input.len());
+            self.pending[self.pending_len..self.pending_len + take].copy_from_slice(&input[..take]);
+            self.pending_len += take;
+            input = &input[take..];
+
+            if self.pending_len < 3 {
+                return Ok(buf.len());
+            }
+
+            let pending = self.pending;
+            self.write_blocks(&pending).map_err(io::Error::other)?;
+            self.pending_len = 0;
+        }
+
+        let (blocks, rest) = input.split_at(input.len() / 3 * 3);
+        self.write_blocks(blocks).map_err(io::Error::other)?;
+
+        self.pending[..rest.len()].copy_from_slice(rest);
+        self.pending_len = rest.len();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Counts the bytes written to it, discarding them.
+struct Counter(usize);
+
+impl Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing() {
+        use std::sync::{Arc, Mutex};
+
+        use tracing::{
+            Event, Metadata,
+            field::{Field, Visit},
+            span,
+        };
+
+        /// Collects the messages of events and the names of spans.
+        #[derive(Clone, Default)]
+        struct Collector(Arc<Mutex<Vec<String>>>);
+
+        impl Visit for Collector {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0.lock().unwrap().push(format!("{value:?}"));
+                }
+            }
+        }
+
+        impl tracing::Subscriber for Collector {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("span {}", span.metadata().name()));
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                event.record(&mut self.clone());
+            }
+
+            fn enter(&self, _: &span::Id) {}
+
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let collector = Collector::default();
+
+        tracing::subscriber::with_default(collector.clone(), || {
+            let buffer = crate::Buffer::new(vec![0; 1000]).with_encoding(BufferEncoding::Base64);
+            let json = serde_json::to_string(&buffer).unwrap();
+            serde_json::from_str::<crate::Buffer>(&json).unwrap();
+
+            let mut json = Vec::new();
+            serialize_bytes(
+                &[0; 1000],
+                &EncodeOptions::new(BufferEncoding::ZBase64),
+                &mut serde_json::Serializer::new(&mut json),
+            )
+            .unwrap();
+            serde_json::from_slice::<crate::Buffer>(&json).unwrap();
+        });
+
+        assert_eq!(
+            *collector.0.lock().unwrap(),
+            [
+                "span serialize_buffer",
+                "chose encoding",
+                "span deserialize_buffer",
+                "decoded base64",
+                "span serialize_buffer",
+                "chose encoding",
+                "compressed buffer",
+                "span deserialize_buffer",
+                "decoded base64",
+                "decompressed buffer",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frame_matches_roblox() {
+        // Exported from Roblox.
+        let roblox = BASE64.decode(b"KLUv/SBfbQAAMGhlbGxvIAEAlqkUAQ==").unwrap();
+
+        assert_eq!(roblox[..4], [0x28, 0xb5, 0x2f, 0xfd], "magic number");
+
+        let descriptor = roblox[4];
+        assert_eq!(descriptor >> 6, 0, "content size is one byte");
+        assert_eq!(descriptor & (1 << 5), 1 << 5, "single segment");
+        assert_eq!(descriptor & (1 << 2), 0, "no checksum");
+        assert_eq!(descriptor & 0b11, 0, "no dictionary");
+        assert_eq!(roblox[5], 95, "content size");
+
+        let data = b"hello hello hello hello hello hello hello hello hello hello hello hello hello hello hello hello";
+        let ours = compress_into(
+            data,
+            &EncodeOptions::new(BufferEncoding::ZBase64),
+            Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(ours, roblox);
+    }
+
+    #[test]
+    fn test_base64_writer() {
+        let data: Vec<u8> = (0..=255).cycle().take(5000).collect();
+
+        for split in [1, 2, 3, 4, 7, 1000] {
+            let mut output = String::new();
+            let mut writer = Base64Writer::new(&mut output);
+            for chunk in data.chunks(split) {
+                writer.write_all(chunk).unwrap();
+            }
+            writer.finish().unwrap();
+
+            assert_eq!(output, BASE64.encode(&data), "{split}");
+        }
+    }
+
+    #[test]
+    fn test_zbase64_streaming_matches_naive() {
+        let data: Vec<u8> = (0..8 * 1024 * 1024).map(|i| (i % 7919) as u8).collect();
+        let options = EncodeOptions::new(BufferEncoding::ZBase64);
+
+        let mut naive = Vec::new();
+        let mut encoder = zstd::stream::Encoder::new(&mut naive, 0).unwrap();
+        encoder
+            .set_pledged_src_size(Some(data.len() as u64))
+            .unwrap();
+        encoder.include_contentsize(true).unwrap();
+        encoder.write_all(&data).unwrap();
+        encoder.finish().unwrap();
+        let naive = BASE64.encode(&naive);
+
+        let mut json = Vec::new();
+        serialize_bytes(&data, &options, &mut serde_json::Serializer::new(&mut json)).unwrap();
+        let json = String::from_utf8(json).unwrap();
+
+        assert_eq!(
+            json,
+            format!(r#"{{"m":null,"t":"buffer","zbase64":"{naive}"}}"#)
+        );
+        assert_eq!(
+            encoded_len(&data, &options).unwrap(),
+            ("zbase64", naive.len())
+        );
+        assert_eq!(
+            serde_json::from_str::<crate::Buffer>(&json)
+                .unwrap()
+                .as_ref(),
+            data
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::io;
+
+use data_encoding::DecodeError;
+
+use crate::{Base64Error, TooLargeError};
+
+/// Returned when decoding or encoding a buffer outside of serde fails.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BufferError {
+    /// The base64 was invalid.
+    Base64(Base64Error),
+    /// Reading or writing failed, or the compressed data was invalid.
+    Io(io::Error),
+    /// The JSON was invalid, or wasn't a buffer.
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+    /// The buffer didn't have the expected length.
+    LengthMismatch {
+        /// The length the buffer was expected to have.
+        expected: usize,
+        /// The length of the decoded buffer.
+        actual: usize,
+    },
+    /// The buffer didn't begin with the expected magic bytes.
+    MagicMismatch {
+        /// The bytes the buffer was expected to begin with.
+        expected: Vec<u8>,
+        /// The bytes the buffer began with instead, at most as many as were expected.
+        actual: Vec<u8>,
+    },
+    /// The buffer was rejected by [`DecodeOptions`](crate::DecodeOptions), rather than being invalid.
+    PolicyViolation(PolicyViolation),
+    /// The buffer decoded to more than the allowed number of bytes.
+    LimitExceeded {
+        /// The maximum number of bytes allowed.
+        max: usize,
+    },
+    /// The signature of the buffer was missing or didn't match.
+    #[cfg(feature = "hmac")]
+    SignatureMismatch,
+    /// The serialized buffer was too large.
+    TooLarge(TooLargeError),
+}
+
+impl std::fmt::Display for BufferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BufferError::Base64(error) => error.fmt(f),
+            BufferError::Io(error) => error.fmt(f),
+            #[cfg(feature = "json")]
+            BufferError::Json(error) => write!(f, "invalid buffer json: {error}"),
+            BufferError::LengthMismatch { expected, actual } => write!(
+                f,
+                "expected a buffer of {expected} bytes, but it was {actual} bytes long"
+            ),
+            BufferError::MagicMismatch { expected, actual } => write!(
+                f,
+                "expected a buffer starting with {expected:02x?}, but it started with {actual:02x?}"
+            ),
+            BufferError::LimitExceeded { max } => {
+                write!(f, "buffer decodes to more than {max} bytes")
+            }
+            BufferError::PolicyViolation(violation) => violation.fmt(f),
+            #[cfg(feature = "hmac")]
+            BufferError::SignatureMismatch => f.write_str("buffer signature doesn't match"),
+            BufferError::TooLarge(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for BufferError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BufferError::Base64(error) => Some(error),
+            BufferError::Io(error) => Some(error),
+            #[cfg(feature = "json")]
+            BufferError::Json(error) => Some(error),
+            BufferError::LengthMismatch { .. }
+            | BufferError::MagicMismatch { .. }
+            | BufferError::LimitExceeded { .. }
+            | BufferError::PolicyViolation(_) => None,
+            #[cfg(feature = "hmac")]
+            BufferError::SignatureMismatch => None,
+            BufferError::TooLarge(error) => Some(error),
+        }
+    }
+}
+
+/// What a buffer was rejected for, in [`BufferError::PolicyViolation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PolicyViolation {
+    /// The buffer was compressed, when [`DecodeOptions::allow_zbase64`](crate::DecodeOptions::allow_zbase64) was
+    /// false.
+    Compressed,
+    /// `t` was missing, when [`DecodeOptions::strict_tag`](crate::DecodeOptions::strict_tag) was true.
+    MissingTag,
+    /// `t` wasn't `buffer`, when [`DecodeOptions::strict_tag`](crate::DecodeOptions::strict_tag) was true.
+    WrongTag,
+    /// The JSON was longer than [`DecodeOptions::max_encoded`](crate::DecodeOptions::max_encoded).
+    TooLarge {
+        /// The length of the JSON.
+        len: usize,
+        /// The maximum length allowed.
+        max: usize,
+    },
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyViolation::Compressed => f.write_str("expected an uncompressed buffer"),
+            PolicyViolation::MissingTag => f.write_str("missing field `t`"),
+            PolicyViolation::WrongTag => f.write_str("expected buffer"),
+            PolicyViolation::TooLarge { len, max } => write!(
+                f,
+                "buffer json is {len} bytes, which is over the limit of {max} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+impl From<Base64Error> for BufferError {
+    fn from(error: Base64Error) -> Self {
+        BufferError::Base64(error)
+    }
+}
+
+impl From<DecodeError> for BufferError {
+    fn from(error: DecodeError) -> Self {
+        BufferError::Base64(error.into())
+    }
+}
+
+impl From<io::Error> for BufferError {
+    fn from(error: io::Error) -> Self {
+        BufferError::Io(error)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for BufferError {
+    fn from(error: serde_json::Error) -> Self {
+        BufferError::Json(error)
+    }
+}
+
+impl From<TooLargeError> for BufferError {
+    fn from(error: TooLargeError) -> Self {
+        BufferError::TooLarge(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_trait() {
+        fn decode(b64: &str) -> Result<usize, Box<dyn std::error::Error>> {
+            Ok(crate::Buffer::base64_decoded_len(b64).map_err(BufferError::from)?)
+        }
+
+        assert_eq!(decode("aGk=").unwrap(), 2);
+        assert_eq!(
+            decode("aGk!").unwrap_err().to_string(),
+            "invalid base64: invalid symbol at 3"
+        );
+    }
+
+    #[test]
+    fn test_from() {
+        let error = data_encoding::BASE64.decode(b"aGk!").unwrap_err();
+        assert!(
+            matches!(BufferError::from(error), BufferError::Base64(base64) if base64.position() == 3)
+        );
+
+        let error = BufferError::from(io::Error::other("oh no"));
+        assert!(matches!(&error, BufferError::Io(io) if io.kind() == io::ErrorKind::Other));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{Buffer, BufferReader, varint};
+
+/// The version written by [`Buffer::to_framed_v2`].
+const VERSION: u8 = 2;
+
+/// The checksum a framed buffer is written with, used by [`Buffer::to_framed_v2`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum FramedChecksum {
+    /// No checksum.
+    None,
+    /// The [`Buffer::crc32`] of the data, as a little endian `u32`.
+    #[default]
+    Crc32,
+}
+
+impl FramedChecksum {
+    fn id(self) -> u8 {
+        match self {
+            FramedChecksum::None => 0,
+            FramedChecksum::Crc32 => 1,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(FramedChecksum::None),
+            1 => Some(FramedChecksum::Crc32),
+            _ => None,
+        }
+    }
+}
+
+/// Returned by [`Buffer::from_framed_v2`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FramedError {
+    /// The frame was written by a version of the format this crate doesn't know.
+    UnsupportedVersion(u8),
+    /// The frame used a checksum this crate doesn't know.
+    UnsupportedChecksum(u8),
+    /// The frame was cut off.
+    UnexpectedEnd,
+    /// The checksum didn't match the data.
+    ChecksumMismatch,
+    /// There were bytes left over after the frame.
+    TrailingBytes {
+        /// The number of bytes left over.
+        len: usize,
+    },
+}
+
+impl std::fmt::Display for FramedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FramedError::UnsupportedVersion(version) => {
+                write!(f, "unsupported framed buffer version {version}")
+            }
+            FramedError::UnsupportedChecksum(id) => {
+                write!(f, "unsupported framed buffer checksum {id}")
+            }
+            FramedError::UnexpectedEnd => f.write_str("framed buffer was cut off"),
+            FramedError::ChecksumMismatch => f.write_str("framed buffer checksum doesn't match"),
+            FramedError::TrailingBytes { len } => {
+                write!(f, "{len} bytes left over after framed buffer")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FramedError {}
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Writes the buffer with a header describing it, to be read with [`Buffer::from_framed_v2`].
+    ///
+    /// The header is a `u8` version, a `u8` checksum id, and then the length of the data as LEB128, the same as
+    /// [`BufferWriter::write_lp_string`](crate::BufferWriter::write_lp_string). The data and then the checksum come
+    /// after. Readers reject versions and checksums they don't know, so later versions can change everything after
+    /// the version byte.
+    pub fn to_framed_v2(&self, checksum: FramedChecksum) -> Buffer {
+        let data = self.0.as_ref();
+
+        let mut framed = Buffer::new(Vec::with_capacity(data.len() + 16));
+        framed.append_u8(VERSION);
+        framed.append_u8(checksum.id());
+        varint::write(&mut framed.0, data.len() as u64);
+        framed.append_string(data);
+
+        match checksum {
+            FramedChecksum::None => {}
+            FramedChecksum::Crc32 => {
+                framed.append_u32(self.crc32());
+            }
+        }
+
+        framed
+    }
+}
+
+impl Buffer {
+    /// Reads a buffer written by [`Buffer::to_framed_v2`], checking its checksum.
+    pub fn from_framed_v2(framed: &[u8]) -> Result<Self, FramedError> {
+        let mut reader = BufferReader::new(framed);
+
+        let [version, checksum] = reader
+            .read_bytes(2)
+            .ok_or(FramedError::UnexpectedEnd)?
+            .try_into()
+            .unwrap();
+
+        if version != VERSION {
+            return Err(FramedError::UnsupportedVersion(version));
+        }
+
+        let checksum =
+            FramedChecksum::from_id(checksum).ok_or(FramedError::UnsupportedChecksum(checksum))?;
+
+        let buffer = Buffer::new(reader.read_lp_string().ok_or(FramedError::UnexpectedEnd)?);
+
+        match checksum {
+            FramedChecksum::None => {}
+            FramedChecksum::Crc32 => {
+                let crc32 = reader.read_uint(4).ok_or(FramedError::UnexpectedEnd)?;
+                if crc32 != u64::from(buffer.crc32()) {
+                    return Err(FramedError::ChecksumMismatch);
+                }
+            }
+        }
+
+        if !reader.is_empty() {
+            return Err(FramedError::TrailingBytes {
+                len: reader.remaining(),
+            });
+        }
+
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let buffer = Buffer::new("hello");
+
+        let framed = buffer.to_framed_v2(FramedChecksum::Crc32);
+        assert_eq!(framed.as_ref()[..3], [2, 1, 5]);
+        assert_eq!(framed.as_ref().len(), 12);
+        assert_eq!(Buffer::from_framed_v2(framed.as_ref()), Ok(buffer.clone()));
+
+        let framed = buffer.to_framed_v2(FramedChecksum::None);
+        assert_eq!(framed.as_ref(), b"\x02\x00\x05hello");
+        assert_eq!(Buffer::from_framed_v2(framed.as_ref()), Ok(buffer));
+
+        let long = Buffer::new(vec![7; 300]);
+        let framed = long.to_framed_v2(FramedChecksum::Crc32);
+        assert_eq!(framed.as_ref()[..4], [2, 1, 0xac, 0x02]);
+        assert_eq!(Buffer::from_framed_v2(framed.as_ref()), Ok(long));
+
+        let empty = Buffer::default().to_framed_v2(FramedChecksum::default());
+        assert_eq!(
+            Buffer::from_framed_v2(empty.as_ref()),
+            Ok(Buffer::default())
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let framed = Buffer::new("hello")
+            .to_framed_v2(FramedChecksum::Crc32)
+            .into_vec();
+
+        let mut bumped = framed.clone();
+        bumped[0] = 3;
+        assert_eq!(
+            Buffer::from_framed_v2(&bumped),
+            Err(FramedError::UnsupportedVersion(3))
+        );
+
+        let mut checksum = framed.clone();
+        checksum[1] = 0xff;
+        assert_eq!(
+            Buffer::from_framed_v2(&checksum),
+            Err(FramedError::UnsupportedChecksum(0xff))
+        );
+
+        let mut tampered = framed.clone();
+        tampered[6] ^= 1;
+        assert_eq!(
+            Buffer::from_framed_v2(&tampered),
+            Err(FramedError::ChecksumMismatch)
+        );
+
+        assert_eq!(
+            Buffer::from_framed_v2(&framed[..framed.len() - 1]),
+            Err(FramedError::UnexpectedEnd)
+        );
+        assert_eq!(Buffer::from_framed_v2(&[]), Err(FramedError::UnexpectedEnd));
+
+        let mut trailing = framed;
+        trailing.push(0);
+        assert_eq!(
+            Buffer::from_framed_v2(&trailing),
+            Err(FramedError::TrailingBytes { len: 1 })
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use data_encoding::BASE64;
+
+use crate::{Buffer, BufferEncoding, EncodeOptions, encode};
+
+/// The same bytes in each encoding, returned by [`Buffer::debug_encodings`] for comparing against Roblox's output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingReport {
+    /// The data of the `base64` encoding.
+    pub base64: String,
+    /// The data of the `zbase64` encoding.
+    pub zbase64: String,
+    /// The length of [`EncodingReport::base64`].
+    pub base64_len: usize,
+    /// The length of [`EncodingReport::zbase64`].
+    pub zbase64_len: usize,
+    /// The header of the zstd frame inside [`EncodingReport::zbase64`], as space separated hex bytes.
+    pub zstd_header: String,
+}
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Encodes the buffer with every encoding, for debugging buffers that don't import into Roblox.
+    pub fn debug_encodings(&self) -> EncodingReport {
+        let frame = encode::compress_into(
+            self.as_ref(),
+            &EncodeOptions::new(BufferEncoding::ZBase64),
+            Vec::new(),
+        )
+        .expect("compressing into memory should not fail");
+
+        let header = &frame[..frame_header_len(&frame).min(frame.len())];
+
+        let base64 = self.to_base64();
+        let zbase64 = BASE64.encode(&frame);
+
+        EncodingReport {
+            base64_len: base64.len(),
+            zbase64_len: zbase64.len(),
+            base64,
+            zbase64,
+            zstd_header: hex(header),
+        }
+    }
+
+    /// Returns a short description of the buffer for logs, with its length, the first and last few bytes in hex,
+    /// and whether it is valid UTF-8, such as `Buffer(len=1048576, 48 65 6c 6c ... 00 01, utf8=false)`.
+    pub fn debug_summary(&self) -> String {
+        // Bytes shown from each end.
+        const PREVIEW_LEN: usize = 4;
+
+        let data = self.as_ref();
+
+        let preview = if data.len() <= PREVIEW_LEN * 2 {
+            hex(data)
+        } else {
+            format!(
+                "{} ... {}",
+                hex(&data[..PREVIEW_LEN]),
+                hex(&data[data.len() - PREVIEW_LEN..])
+            )
+        };
+
+        let preview = if data.is_empty() {
+            String::new()
+        } else {
+            format!("{preview}, ")
+        };
+
+        format!(
+            "Buffer(len={}, {preview}utf8={})",
+            data.len(),
+            std::str::from_utf8(data).is_ok()
+        )
+    }
+}
+
+// Lowercase hex bytes separated by spaces.
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The length of the magic number and frame header at the start of a zstd frame.
+fn frame_header_len(frame: &[u8]) -> usize {
+    let Some(&descriptor) = frame.get(4) else {
+        return frame.len();
+    };
+
+    let single_segment = descriptor & (1 << 5) != 0;
+    let window_len = if single_segment { 0 } else { 1 };
+    let dictionary_len = [0, 1, 2, 4][(descriptor & 0b11) as usize];
+    let content_size_len = match descriptor >> 6 {
+        0 if single_segment => 1,
+        0 => 0,
+        1 => 2,
+        2 => 4,
+        _ => 8,
+    };
+
+    5 + window_len + dictionary_len + content_size_len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_encodings() {
+        let report = Buffer::new("hello world").debug_encodings();
+
+        assert_eq!(report.base64, "aGVsbG8gd29ybGQ=");
+        assert_eq!(report.base64_len, 16);
+        assert_eq!(report.zbase64_len, report.zbase64.len());
+        assert!(report.zstd_header.starts_with("28 b5 2f fd"));
+
+        // Single segment with a one byte content size of 11.
+        assert_eq!(report.zstd_header, "28 b5 2f fd 20 0b");
+
+        let zbase64 = format!(
+            r#"{{"m":null,"t":"buffer","zbase64":"{}"}}"#,
+            report.zbase64
+        );
+        assert_eq!(
+            serde_json::from_str::<Buffer>(&zbase64).unwrap(),
+            Buffer::new("hello world")
+        );
+    }
+
+    #[test]
+    fn test_debug_summary() {
+        assert_eq!(
+            Buffer::new("Hello, world\x00\x01").debug_summary(),
+            "Buffer(len=14, 48 65 6c 6c ... 6c 64 00 01, utf8=true)"
+        );
+        assert_eq!(
+            Buffer::new([0xff, 0]).debug_summary(),
+            "Buffer(len=2, ff 00, utf8=false)"
+        );
+        assert_eq!(
+            Buffer::default().debug_summary(),
+            "Buffer(len=0, utf8=true)"
+        );
+    }
+}
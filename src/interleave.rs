@@ -0,0 +1,128 @@
+use crate::Buffer;
+
+/// Returned by [`Buffer::deinterleave`] and [`Buffer::interleave`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterleaveError {
+    /// The stride was 0.
+    ZeroStride,
+    /// The buffer wasn't made up of whole strides.
+    LengthNotMultiple {
+        /// The length of the buffer.
+        len: usize,
+        /// The stride it was split by.
+        stride: usize,
+    },
+    /// The planes weren't all the same length.
+    UnequalPlanes,
+}
+
+impl std::fmt::Display for InterleaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InterleaveError::ZeroStride => f.write_str("stride must not be 0"),
+            InterleaveError::LengthNotMultiple { len, stride } => write!(
+                f,
+                "buffer of {len} bytes can't be split into strides of {stride} bytes"
+            ),
+            InterleaveError::UnequalPlanes => f.write_str("planes must all be the same length"),
+        }
+    }
+}
+
+impl std::error::Error for InterleaveError {}
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Splits interleaved bytes into `stride` planes, where byte `i` goes to plane `i % stride`.
+    /// The inverse of [`Buffer::interleave`].
+    pub fn deinterleave(&self, stride: usize) -> Result<Vec<Buffer>, InterleaveError> {
+        let data = self.0.as_ref();
+
+        if stride == 0 {
+            return Err(InterleaveError::ZeroStride);
+        }
+
+        if !data.len().is_multiple_of(stride) {
+            return Err(InterleaveError::LengthNotMultiple {
+                len: data.len(),
+                stride,
+            });
+        }
+
+        let mut planes = vec![Vec::with_capacity(data.len() / stride); stride];
+        for chunk in data.chunks_exact(stride) {
+            for (plane, &byte) in planes.iter_mut().zip(chunk) {
+                plane.push(byte);
+            }
+        }
+
+        Ok(planes.into_iter().map(Buffer::new).collect())
+    }
+}
+
+impl Buffer {
+    /// Interleaves planes of the same length, taking a byte from each plane in turn.
+    /// The inverse of [`Buffer::deinterleave`].
+    pub fn interleave<U: AsRef<[u8]>>(planes: &[Buffer<U>]) -> Result<Buffer, InterleaveError> {
+        let Some(first) = planes.first() else {
+            return Ok(Buffer::default());
+        };
+
+        let plane_len = first.as_ref().len();
+        if planes.iter().any(|plane| plane.as_ref().len() != plane_len) {
+            return Err(InterleaveError::UnequalPlanes);
+        }
+
+        let mut data = Vec::with_capacity(plane_len * planes.len());
+        for index in 0..plane_len {
+            data.extend(planes.iter().map(|plane| plane.as_ref()[index]));
+        }
+
+        Ok(Buffer::new(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let buffer = Buffer::new([1, 2, 3, 4, 5, 6]);
+
+        let planes = buffer.deinterleave(3).unwrap();
+        assert_eq!(
+            planes,
+            [
+                Buffer::new([1, 4]),
+                Buffer::new([2, 5]),
+                Buffer::new([3, 6])
+            ]
+        );
+        assert_eq!(Buffer::interleave(&planes), Ok(buffer.clone()));
+
+        assert_eq!(
+            buffer.deinterleave(1).unwrap(),
+            std::slice::from_ref(&buffer)
+        );
+        assert_eq!(
+            Buffer::default().deinterleave(2).unwrap(),
+            [Buffer::default(), Buffer::default()]
+        );
+        assert_eq!(Buffer::interleave::<Vec<u8>>(&[]), Ok(Buffer::default()));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let buffer = Buffer::new([1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(buffer.deinterleave(0), Err(InterleaveError::ZeroStride));
+        assert_eq!(
+            buffer.deinterleave(4),
+            Err(InterleaveError::LengthNotMultiple { len: 6, stride: 4 })
+        );
+        assert_eq!(
+            Buffer::interleave(&[Buffer::new([1, 2]), Buffer::new([3])]),
+            Err(InterleaveError::UnequalPlanes)
+        );
+    }
+}
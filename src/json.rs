@@ -0,0 +1,626 @@
+//! Helpers for buffers stored as JSON, enabled with the `json` feature.
+
+use std::io::{self, Write};
+
+use crate::{Buffer, BufferEncoding, BufferError, base64::Base64Reader, de::Compression};
+
+/// A report about a buffer read from JSON, returned by [`Buffer::audit_json`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferAudit {
+    /// The length of the decoded buffer.
+    pub len: usize,
+    /// The encoding the buffer was stored with.
+    pub encoding_detected: BufferEncoding,
+    /// The [`Buffer::crc32`] of the decoded buffer.
+    pub crc32: u32,
+    /// The [`Buffer::shannon_entropy`] of the decoded buffer.
+    pub entropy: f64,
+    /// Whether the decoded buffer is valid UTF-8.
+    pub looks_utf8: bool,
+    /// Whether the decoded buffer starts with the zstd magic number, such as when it was compressed before encoding.
+    pub looks_zstd: bool,
+}
+
+/// Options for reading a buffer from untrusted JSON, such as a request body.
+///
+/// ```
+/// let buffer = roblox_buffer::DecodeOptions::new()
+///     .max_encoded(1024)
+///     .max_decoded(64)
+///     .decode_json(r#"{"m":null,"t":"buffer","base64":"aGVsbG8="}"#)
+///     .unwrap();
+/// assert_eq!(buffer, roblox_buffer::Buffer::new("hello"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeOptions {
+    max_encoded: usize,
+    max_decoded: usize,
+    allow_zbase64: bool,
+    strict_tag: bool,
+}
+
+impl DecodeOptions {
+    /// Creates options that accept anything deserializing a [`Buffer`] does.
+    pub fn new() -> Self {
+        Self {
+            max_encoded: usize::MAX,
+            max_decoded: crate::MAX_BUFFER_LEN,
+            allow_zbase64: true,
+            strict_tag: true,
+        }
+    }
+
+    /// Fails with [`PolicyViolation::TooLarge`](crate::PolicyViolation::TooLarge) before parsing JSON longer than
+    /// `max` bytes.
+    pub fn max_encoded(mut self, max: usize) -> Self {
+        self.max_encoded = max;
+        self
+    }
+
+    /// Fails with [`BufferError::LimitExceeded`] instead of decoding or decompressing to more than `max` bytes.
+    /// Defaults to [`MAX_BUFFER_LEN`](crate::MAX_BUFFER_LEN).
+    pub fn max_decoded(mut self, max: usize) -> Self {
+        self.max_decoded = max;
+        self
+    }
+
+    /// Whether compressed buffers are accepted, including `data` with zstd compression. Defaults to true.
+    /// Compressed buffers fail with [`PolicyViolation::Compressed`](crate::PolicyViolation::Compressed) otherwise.
+    pub fn allow_zbase64(mut self, allow: bool) -> Self {
+        self.allow_zbase64 = allow;
+        self
+    }
+
+    /// Whether `t` has to be present and `buffer`. Defaults to true, the same as deserializing a [`Buffer`].
+    /// Buffers without it fail with [`BufferError::PolicyViolation`].
+    pub fn strict_tag(mut self, strict: bool) -> Self {
+        self.strict_tag = strict;
+        self
+    }
+
+    /// Reads a buffer from JSON within these options.
+    pub fn decode_json(&self, s: &str) -> Result<Buffer, BufferError> {
+        if s.len() > self.max_encoded {
+            return Err(BufferError::PolicyViolation(
+                crate::PolicyViolation::TooLarge {
+                    len: s.len(),
+                    max: self.max_encoded,
+                },
+            ));
+        }
+
+        crate::de::decode_json(
+            s,
+            &crate::de::Limits {
+                max_decoded: self.max_decoded,
+                allow_compressed: self.allow_zbase64,
+                strict_tag: self.strict_tag,
+                report_policy: true,
+            },
+        )
+        .map(|(buffer, _)| buffer)
+    }
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Buffer {
+    /// Reads a buffer from JSON, checking that it decodes to exactly `expected_len` bytes.
+    pub fn from_json_str_exact(s: &str, expected_len: usize) -> Result<Self, BufferError> {
+        let buffer: Buffer = serde_json::from_str(s)?;
+
+        if buffer.0.len() != expected_len {
+            return Err(BufferError::LengthMismatch {
+                expected: expected_len,
+                actual: buffer.0.len(),
+            });
+        }
+
+        Ok(buffer)
+    }
+
+    /// Reads a buffer from JSON, checking that it begins with the magic bytes of the expected format.
+    pub fn from_json_str_expecting_magic(s: &str, magic: &[u8]) -> Result<Self, BufferError> {
+        let buffer: Buffer = serde_json::from_str(s)?;
+
+        if !buffer.starts_with(magic) {
+            return Err(BufferError::MagicMismatch {
+                expected: magic.to_vec(),
+                actual: buffer.0[..magic.len().min(buffer.0.len())].to_vec(),
+            });
+        }
+
+        Ok(buffer)
+    }
+
+    /// Reads buffer JSON from messy input and writes it back out in the form of [`Buffer::to_canonical_json`].
+    ///
+    /// Accepts anything [`lenient`](crate::lenient) does, with the keys in any order and `m` missing. The base64 may
+    /// also contain whitespace, be missing padding, or use the URL safe alphabet.
+    pub fn repair_json(input: &str) -> Result<String, BufferError> {
+        let mut value: serde_json::Value = serde_json::from_str(input)?;
+
+        match &mut value {
+            serde_json::Value::String(base64) => *base64 = repair_base64(base64),
+            serde_json::Value::Object(object) => {
+                for key in ["base64", "zbase64", "data"] {
+                    if let Some(serde_json::Value::String(base64)) = object.get_mut(key) {
+                        *base64 = repair_base64(base64);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(crate::lenient::deserialize(value)?.to_canonical_json())
+    }
+
+    /// Decodes buffer JSON straight into `out`, decompressing it as it goes, without holding the buffer in
+    /// memory. Returns the number of bytes written.
+    ///
+    /// Accepts the same JSON as deserializing a [`Buffer`] does. Fails with [`BufferError::LimitExceeded`] instead
+    /// of writing more than `max` bytes. That and invalid JSON, base64, or length are [`io::ErrorKind::InvalidData`]
+    /// errors holding a [`BufferError`], while invalid compressed data is the error from the decompressor.
+    pub fn transcode_json_to_writer<W: Write>(json: &str, out: W, max: usize) -> io::Result<u64> {
+        let invalid_data = |error: BufferError| io::Error::new(io::ErrorKind::InvalidData, error);
+
+        let envelope = crate::de::open_json(json).map_err(invalid_data)?;
+
+        let mut out = LimitedWriter {
+            inner: out,
+            written: 0,
+            max,
+        };
+
+        let mut data = Base64Reader::new(&envelope.base64);
+        match envelope.compression {
+            Compression::None => io::copy(&mut data, &mut out)?,
+            Compression::Zstd => io::copy(&mut zstd::stream::Decoder::new(data)?, &mut out)?,
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => io::copy(&mut flate2::read::MultiGzDecoder::new(data), &mut out)?,
+        };
+
+        if let Some(len) = envelope.len
+            && len as u64 != out.written
+        {
+            return Err(invalid_data(BufferError::LengthMismatch {
+                expected: len,
+                actual: out.written as usize,
+            }));
+        }
+
+        Ok(out.written)
+    }
+
+    /// Reads a buffer from JSON and reports what it contains, for inspecting buffers.
+    pub fn audit_json(s: &str) -> Result<BufferAudit, BufferError> {
+        let (buffer, encoding_detected) = crate::de::decode_json(s, &crate::de::Limits::DEFAULT)?;
+
+        Ok(BufferAudit {
+            len: buffer.0.len(),
+            encoding_detected,
+            crc32: buffer.crc32(),
+            entropy: buffer.shannon_entropy(),
+            looks_utf8: std::str::from_utf8(&buffer.0).is_ok(),
+            looks_zstd: buffer.0.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]),
+        })
+    }
+}
+
+// Fails instead of writing more than `max` bytes.
+struct LimitedWriter<W> {
+    inner: W,
+    written: u64,
+    max: usize,
+}
+
+impl<W: Write> Write for LimitedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written + buf.len() as u64 > self.max as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                BufferError::LimitExceeded { max: self.max },
+            ));
+        }
+
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Converts base64 with whitespace, missing padding, or the URL safe alphabet to standard base64.
+fn repair_base64(base64: &str) -> String {
+    let mut repaired: String = base64
+        .chars()
+        .filter(|char| !char.is_ascii_whitespace())
+        .map(|char| match char {
+            '-' => '+',
+            '_' => '/',
+            char => char,
+        })
+        .collect();
+
+    while !repaired.len().is_multiple_of(4) {
+        repaired.push('=');
+    }
+
+    repaired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json_str_exact() {
+        let json = r#"{"m":null,"t":"buffer","base64":"aGVsbG8="}"#;
+
+        assert_eq!(
+            Buffer::from_json_str_exact(json, 5).unwrap(),
+            Buffer::new("hello")
+        );
+
+        assert!(matches!(
+            Buffer::from_json_str_exact(json, 6),
+            Err(BufferError::LengthMismatch {
+                expected: 6,
+                actual: 5
+            })
+        ));
+
+        let error = Buffer::from_json_str_exact(json, 4).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected a buffer of 4 bytes, but it was 5 bytes long"
+        );
+
+        assert!(matches!(
+            Buffer::from_json_str_exact("{}", 0),
+            Err(BufferError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_audit_json() {
+        let audit =
+            Buffer::audit_json(r#"{"m":null,"t":"buffer","base64":"aGVsbG8gd29ybGQ="}"#).unwrap();
+        assert_eq!(audit.len, 11);
+        assert_eq!(audit.encoding_detected, BufferEncoding::Base64);
+        assert_eq!(audit.crc32, Buffer::new("hello world").crc32());
+        assert_eq!(audit.entropy, Buffer::new("hello world").shannon_entropy());
+        assert!(audit.looks_utf8);
+        assert!(!audit.looks_zstd);
+
+        let audit = Buffer::audit_json(
+            r#"{"m":null,"t":"buffer","zbase64":"KLUv/SBfbQAAMGhlbGxvIAEAlqkUAQ=="}"#,
+        )
+        .unwrap();
+        assert_eq!(audit.len, 95);
+        assert_eq!(audit.encoding_detected, BufferEncoding::ZBase64);
+        assert!(audit.looks_utf8);
+        assert!(!audit.looks_zstd);
+
+        // Compressed data stored uncompressed.
+        let audit = Buffer::audit_json(
+            r#"{"m":null,"t":"buffer","base64":"KLUv/SBfbQAAMGhlbGxvIAEAlqkUAQ=="}"#,
+        )
+        .unwrap();
+        assert_eq!(audit.encoding_detected, BufferEncoding::Base64);
+        assert!(!audit.looks_utf8);
+        assert!(audit.looks_zstd);
+
+        // The first data key is the one decoded, so it's the one reported.
+        let audit = Buffer::audit_json(
+            r#"{"m":null,"t":"buffer","base64":"aGk=","zbase64":"KLUv/SBfbQAAMGhlbGxvIAEAlqkUAQ=="}"#,
+        )
+        .unwrap();
+        assert_eq!(audit.len, 2);
+        assert_eq!(audit.encoding_detected, BufferEncoding::Base64);
+
+        assert!(matches!(
+            Buffer::audit_json("{}"),
+            Err(BufferError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_repair_json() {
+        // 0xfb 0xff 0xfe encodes to characters that differ in the URL safe alphabet.
+        let canonical = Buffer::new(b"hi\xfb\xff\xfe".to_vec()).to_canonical_json();
+        assert_eq!(canonical, r#"{"m":null,"t":"buffer","base64":"aGn7//4="}"#);
+
+        for messy in [
+            r#"{"m":null,"t":"buffer","base64":"aGn7//4="}"#,
+            r#"{"base64":"aGn7//4=","t":"buffer"}"#,
+            r#" { "t" : "buffer", "m" : null, "base64" : "aGn7\n//4=" } "#,
+            r#"{"t":"buffer","base64":"aGn7__4"}"#,
+            r#"{"t":"buffer","base64":"aGn7 __4="}"#,
+            r#""aGn7__4""#,
+            "[104, 105, 251, 255, 254]",
+        ] {
+            assert_eq!(Buffer::repair_json(messy).unwrap(), canonical, "{messy}");
+        }
+
+        let zbase64 = serde_json::to_string(
+            &Buffer::new(b"hi\xfb\xff\xfe".to_vec()).with_encoding(BufferEncoding::ZBase64),
+        )
+        .unwrap();
+        assert_eq!(Buffer::repair_json(&zbase64).unwrap(), canonical);
+
+        assert!(matches!(
+            Buffer::repair_json(r#"{"t":"buffer","base64":"a!"}"#),
+            Err(BufferError::Json(_))
+        ));
+        assert!(Buffer::repair_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_from_json_str_expecting_magic() {
+        let json = r#"{"m":null,"t":"buffer","base64":"aGVsbG8="}"#;
+
+        assert_eq!(
+            Buffer::from_json_str_expecting_magic(json, b"hell").unwrap(),
+            Buffer::new("hello")
+        );
+
+        let error = Buffer::from_json_str_expecting_magic(json, b"help").unwrap_err();
+        assert!(matches!(
+            &error,
+            BufferError::MagicMismatch { expected, actual } if expected == b"help" && actual == b"hell"
+        ));
+        assert_eq!(
+            error.to_string(),
+            "expected a buffer starting with [68, 65, 6c, 70], but it started with [68, 65, 6c, 6c]"
+        );
+
+        assert!(matches!(
+            Buffer::from_json_str_expecting_magic(json, b"hello world"),
+            Err(BufferError::MagicMismatch { actual, .. }) if actual == b"hello"
+        ));
+    }
+
+    #[test]
+    fn test_transcode_json_to_writer() {
+        let hello = Buffer::new(b"hello ".repeat(1000));
+
+        for encoding in [BufferEncoding::Base64, BufferEncoding::ZBase64] {
+            let json = serde_json::to_string(&hello.clone().with_encoding(encoding)).unwrap();
+
+            let mut out = Vec::new();
+            assert_eq!(
+                Buffer::transcode_json_to_writer(&json, &mut out, usize::MAX).unwrap(),
+                6000
+            );
+            assert_eq!(out, hello.as_ref());
+
+            let error = Buffer::transcode_json_to_writer(&json, io::sink(), 5999).unwrap_err();
+            assert!(matches!(
+                error
+                    .into_inner()
+                    .unwrap()
+                    .downcast::<BufferError>()
+                    .as_deref(),
+                Ok(BufferError::LimitExceeded { max: 5999 })
+            ));
+        }
+
+        let mut out = Vec::new();
+        Buffer::transcode_json_to_writer(
+            r#"{"m":null,"t":"buffer","base64":"aGVsbG8="}"#,
+            &mut out,
+            5,
+        )
+        .unwrap();
+        assert_eq!(out, b"hello");
+    }
+
+    #[test]
+    fn test_transcode_json_to_writer_invalid() {
+        for json in [
+            r#"{"m":null,"t":"buffer","base64":"aGVsbG8"}"#,
+            r#"{"m":null,"t":"string","base64":"aGVsbG8="}"#,
+            r#"{"m":null,"t":"buffer"}"#,
+            "{",
+        ] {
+            let error = Buffer::transcode_json_to_writer(json, io::sink(), usize::MAX).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{json}");
+        }
+
+        let zbase64 = r#"{"m":null,"t":"buffer","zbase64":"aGVsbG8="}"#;
+        assert!(Buffer::transcode_json_to_writer(zbase64, io::sink(), usize::MAX).is_err());
+
+        let base64 = "A".repeat(4000) + "!";
+        let json = format!(r#"{{"m":null,"t":"buffer","base64":"{base64}"}}"#);
+        let error = Buffer::transcode_json_to_writer(&json, io::sink(), usize::MAX).unwrap_err();
+        assert!(matches!(
+            error.into_inner().unwrap().downcast::<BufferError>().as_deref(),
+            Ok(BufferError::Base64(base64)) if base64.position() == 4000
+        ));
+    }
+
+    #[test]
+    fn test_transcode_json_to_writer_matches_deserialize() {
+        let zstd = crate::encode::compress_into(
+            b"hello",
+            &crate::EncodeOptions::new(BufferEncoding::ZBase64),
+            Vec::new(),
+        )
+        .unwrap();
+        let zstd = data_encoding::BASE64.encode(&zstd);
+
+        for json in [
+            r#"{"m":null,"t":"buffer","len":5,"base64":"aGVsbG8="}"#.to_owned(),
+            r#"{"m":null,"t":"buffer","len":4,"base64":"aGVsbG8="}"#.to_owned(),
+            r#"{"t":"buffer","data":"aGVsbG8="}"#.to_owned(),
+            format!(r#"{{"t":"buffer","data":"{zstd}","compression":"zstd"}}"#),
+            r#"{"t":"buffer","base64":"aGVsbG8=","compression":"zstd"}"#.to_owned(),
+            r#"{"m":null,"base64":"aGVsbG8="}"#.to_owned(),
+        ] {
+            let mut out = Vec::new();
+            let transcoded = Buffer::transcode_json_to_writer(&json, &mut out, usize::MAX)
+                .map(|_| Buffer::new(out));
+
+            match serde_json::from_str::<Buffer>(&json) {
+                Ok(buffer) => assert_eq!(transcoded.unwrap(), buffer, "{json}"),
+                Err(_) => assert!(transcoded.is_err(), "{json}"),
+            }
+        }
+
+        let error = Buffer::transcode_json_to_writer(
+            r#"{"m":null,"t":"buffer","len":4,"base64":"aGVsbG8="}"#,
+            io::sink(),
+            usize::MAX,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error
+                .into_inner()
+                .unwrap()
+                .downcast::<BufferError>()
+                .as_deref(),
+            Ok(BufferError::LengthMismatch {
+                expected: 4,
+                actual: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn test_decode_options() {
+        let hello = Buffer::new("hello");
+        let base64 = r#"{"m":null,"t":"buffer","base64":"aGVsbG8="}"#;
+        let zbase64 =
+            serde_json::to_string(&hello.clone().with_encoding(BufferEncoding::ZBase64)).unwrap();
+        assert!(zbase64.contains("zbase64"));
+
+        let options = DecodeOptions::new();
+        assert_eq!(options.decode_json(base64).unwrap(), hello);
+        assert_eq!(options.decode_json(&zbase64).unwrap(), hello);
+
+        assert!(matches!(
+            DecodeOptions::new().max_encoded(10).decode_json(base64),
+            Err(BufferError::PolicyViolation(
+                crate::PolicyViolation::TooLarge { len, max: 10 }
+            )) if len == base64.len()
+        ));
+        assert_eq!(
+            DecodeOptions::new()
+                .max_encoded(base64.len())
+                .decode_json(base64)
+                .unwrap(),
+            hello
+        );
+
+        assert_eq!(
+            DecodeOptions::new()
+                .max_decoded(5)
+                .decode_json(base64)
+                .unwrap(),
+            hello
+        );
+        assert!(matches!(
+            DecodeOptions::new().max_decoded(4).decode_json(base64),
+            Err(BufferError::LimitExceeded { max: 4 })
+        ));
+        assert!(matches!(
+            DecodeOptions::new().max_decoded(4).decode_json(&zbase64),
+            Err(BufferError::LimitExceeded { max: 4 })
+        ));
+
+        let large = serde_json::to_string(
+            &Buffer::new(vec![0; 100_000]).with_encoding(BufferEncoding::ZBase64),
+        )
+        .unwrap();
+        assert!(large.len() < 1000);
+        assert!(matches!(
+            DecodeOptions::new().max_decoded(1000).decode_json(&large),
+            Err(BufferError::LimitExceeded { max: 1000 })
+        ));
+
+        assert!(matches!(
+            DecodeOptions::new()
+                .allow_zbase64(false)
+                .decode_json(&zbase64),
+            Err(BufferError::PolicyViolation(
+                crate::PolicyViolation::Compressed
+            ))
+        ));
+        assert!(matches!(
+            DecodeOptions::new()
+                .allow_zbase64(false)
+                .decode_json(r#"{"t":"buffer","data":"aGVsbG8=","compression":"zstd"}"#),
+            Err(BufferError::PolicyViolation(
+                crate::PolicyViolation::Compressed
+            ))
+        ));
+        assert!(matches!(
+            DecodeOptions::new()
+                .allow_zbase64(false)
+                .decode_json(r#"{"t":"buffer","#),
+            Err(BufferError::Json(_))
+        ));
+        assert_eq!(
+            DecodeOptions::new()
+                .allow_zbase64(false)
+                .decode_json(base64)
+                .unwrap(),
+            hello
+        );
+
+        let untagged = r#"{"base64":"aGVsbG8="}"#;
+        let mistagged = r#"{"t":"buf","base64":"aGVsbG8="}"#;
+        assert!(matches!(
+            DecodeOptions::new().decode_json(untagged),
+            Err(BufferError::PolicyViolation(
+                crate::PolicyViolation::MissingTag
+            ))
+        ));
+        assert!(matches!(
+            DecodeOptions::new().decode_json(mistagged),
+            Err(BufferError::PolicyViolation(
+                crate::PolicyViolation::WrongTag
+            ))
+        ));
+        let loose = DecodeOptions::new().strict_tag(false);
+        assert_eq!(loose.decode_json(untagged).unwrap(), hello);
+        assert_eq!(loose.decode_json(mistagged).unwrap(), hello);
+
+        let combined = DecodeOptions::new()
+            .max_encoded(1000)
+            .max_decoded(5)
+            .allow_zbase64(false)
+            .strict_tag(false);
+        assert_eq!(combined.decode_json(untagged).unwrap(), hello);
+        assert!(matches!(
+            combined.decode_json(&zbase64),
+            Err(BufferError::PolicyViolation(
+                crate::PolicyViolation::Compressed
+            ))
+        ));
+        assert!(matches!(
+            combined.decode_json(&large),
+            Err(BufferError::PolicyViolation(
+                crate::PolicyViolation::Compressed
+            ))
+        ));
+        assert!(matches!(
+            combined.decode_json(r#"{"base64":"aGVsbG8h"}"#),
+            Err(BufferError::LimitExceeded { max: 5 })
+        ));
+        assert!(matches!(
+            combined.decode_json(&format!(r#"{{"base64":"{}"}}"#, "A".repeat(1000))),
+            Err(BufferError::PolicyViolation(
+                crate::PolicyViolation::TooLarge { max: 1000, .. }
+            ))
+        ));
+    }
+}
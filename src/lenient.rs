@@ -0,0 +1,169 @@
+//! Deserializes a Roblox buffer from any of the forms buffers are commonly mistaken for, for use with
+//! `#[serde(deserialize_with = "roblox_buffer::lenient::deserialize")]`.
+//!
+//! Accepts a buffer in any encoding [`Buffer`] does, a bare base64 string without the envelope, or an array of bytes.
+//! Inside the envelope, `base64` and `zbase64` can also be arrays of bytes instead of base64, which for `zbase64` are
+//! the compressed bytes. Base64 can be followed by anything starting with a character that isn't base64, such as a
+//! `#checksum` comment, which is ignored.
+//!
+//! ```
+//! #[derive(serde::Deserialize)]
+//! struct Save {
+//!     #[serde(deserialize_with = "roblox_buffer::lenient::deserialize")]
+//!     data: roblox_buffer::Buffer,
+//! }
+//! ```
+
+use serde::{Deserialize, Deserializer, de};
+
+use crate::Buffer;
+
+/// Deserializes a Roblox buffer, bare base64, or an array of bytes.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Buffer, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_with_trailer(deserializer).map(|(buffer, _)| buffer)
+}
+
+/// Deserializes the same as [`deserialize`], also returning anything that came after the base64, such as a
+/// `#checksum` comment.
+///
+/// Base64 ends at the first character that can't be part of it. The rest is ignored by [`deserialize`], while
+/// [`Buffer`] on its own fails to decode it.
+pub fn deserialize_with_trailer<'de, D>(
+    deserializer: D,
+) -> Result<(Buffer, Option<String>), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct LenientVisitor;
+
+    impl<'de> de::Visitor<'de> for LenientVisitor {
+        type Value = (Buffer, Option<String>);
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a buffer, a base64 string, or an array of bytes")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            let (base64, trailer) = crate::de::split_trailer(v);
+
+            crate::base64::decode_base64(base64.as_bytes())
+                .map(|data| (Buffer::new(data), trailer.map(str::to_owned)))
+                .map_err(E::custom)
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok((Buffer::new(v), None))
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            Ok((Buffer::new(v), None))
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            Vec::<u8>::deserialize(de::value::SeqAccessDeserializer::new(seq))
+                .map(|data| (Buffer::new(data), None))
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            crate::de::deserialize_envelope_with_trailer(
+                de::value::MapAccessDeserializer::new(map),
+                true,
+            )
+        }
+    }
+
+    deserializer.deserialize_any(LenientVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::Buffer;
+
+    #[derive(Debug, Deserialize)]
+    struct Save {
+        #[serde(deserialize_with = "crate::lenient::deserialize")]
+        data: Buffer,
+    }
+
+    fn data(json: &str) -> Result<Buffer, serde_json::Error> {
+        serde_json::from_str::<Save>(&format!(r#"{{"data":{json}}}"#)).map(|save| save.data)
+    }
+
+    #[test]
+    fn test_lenient() {
+        let hello = Buffer::new("hello");
+
+        assert_eq!(
+            data(r#"{"m":null,"t":"buffer","base64":"aGVsbG8="}"#).unwrap(),
+            hello
+        );
+        let zbase64 =
+            serde_json::to_string(&hello.clone().with_encoding(crate::BufferEncoding::ZBase64))
+                .unwrap();
+        assert!(zbase64.contains("zbase64"));
+        assert_eq!(data(&zbase64).unwrap(), hello);
+        assert_eq!(data(r#""aGVsbG8=""#).unwrap(), hello);
+        assert_eq!(data("[104, 101, 108, 108, 111]").unwrap(), hello);
+        assert_eq!(data("[]").unwrap(), Buffer::default());
+
+        assert_eq!(
+            data(r#"{"m":null,"t":"buffer","base64":[104, 101, 108, 108, 111]}"#).unwrap(),
+            hello
+        );
+
+        let compressed = crate::encode::compress_into(
+            b"hello",
+            &crate::EncodeOptions::new(crate::BufferEncoding::ZBase64),
+            Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            data(&format!(
+                r#"{{"m":null,"t":"buffer","zbase64":{compressed:?}}}"#
+            ))
+            .unwrap(),
+            hello
+        );
+
+        let error = data(r#"{"m":null,"t":"buffer","base64":[104, 1000]}"#).unwrap_err();
+        assert!(error.to_string().contains("1000"), "{error}");
+
+        assert!(data(r#""aGVsbG8""#).is_err());
+        assert!(data("[104, 256]").is_err());
+        assert!(data("5").is_err());
+    }
+
+    #[test]
+    fn test_trailer() {
+        let hello = Buffer::new("hello");
+
+        assert_eq!(
+            data(r#"{"m":null,"t":"buffer","base64":"aGVsbG8=#checksum"}"#).unwrap(),
+            hello
+        );
+        assert_eq!(data(r#""aGVsbG8=#checksum""#).unwrap(), hello);
+        assert_eq!(data(r#""aGk=;aGk=""#).unwrap(), Buffer::new("hi"));
+
+        let json = r#"{"m":null,"t":"buffer","base64":"aGVsbG8=#1234"}"#;
+        assert!(serde_json::from_str::<Buffer>(json).is_err());
+
+        let (buffer, trailer) =
+            crate::lenient::deserialize_with_trailer(&mut serde_json::Deserializer::from_str(json))
+                .unwrap();
+        assert_eq!(buffer, hello);
+        assert_eq!(trailer.as_deref(), Some("#1234"));
+
+        let (_, trailer) = crate::lenient::deserialize_with_trailer(
+            &mut serde_json::Deserializer::from_str(r#""aGVsbG8=""#),
+        )
+        .unwrap();
+        assert_eq!(trailer, None);
+
+        assert!(data(r#""aGVsbG8#1234""#).is_err());
+    }
+}
@@ -2,19 +2,214 @@
 //! This library exposes [`Buffer`] that serializes and deserializes to the `buffer` type in Roblox.
 #![warn(missing_docs)]
 
-use std::io::{Read, Write};
+/// Emits a `tracing` debug event when the `tracing` feature is enabled, and compiles to nothing otherwise.
+macro_rules! debug_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+mod access;
+mod analysis;
+pub mod as_base64;
+pub mod as_zbase64;
+mod base64;
+pub mod borrowed;
+#[cfg(feature = "bytemuck")]
+mod cast;
+mod compact;
+#[cfg(feature = "json")]
+mod compat;
+mod de;
+mod encode;
+mod error;
+mod framed;
+mod inspect;
+mod interleave;
+#[cfg(feature = "json")]
+mod json;
+pub mod lenient;
+#[cfg(feature = "mmap")]
+mod mmap;
+pub mod patch;
+mod pool;
+mod reader;
+mod records;
+#[cfg(feature = "hmac")]
+mod signed;
+#[cfg(feature = "sorted-set")]
+mod sorted;
+#[cfg(feature = "json")]
+pub mod stringified;
+mod tlv;
+mod varint;
+mod writer;
+#[cfg(feature = "z85")]
+mod z85;
+
+use std::{borrow::Cow, collections::TryReserveError, io::Read, ops::RangeBounds};
 
 use data_encoding::BASE64;
 use serde::{Deserialize, Serialize};
 
+pub use access::OutOfBounds;
+pub use base64::{Base64Accumulator, Base64Error};
+#[cfg(feature = "bytemuck")]
+pub use cast::CastError;
+pub use compact::CompactBuffer;
+#[cfg(feature = "json")]
+pub use compat::CompatibilityIssue;
+pub use error::{BufferError, PolicyViolation};
+pub use framed::{FramedChecksum, FramedError};
+pub use inspect::EncodingReport;
+pub use interleave::InterleaveError;
+#[cfg(feature = "json")]
+pub use json::{BufferAudit, DecodeOptions};
+#[cfg(feature = "mmap")]
+pub use mmap::MmappedBuffer;
+pub use patch::PatchError;
+pub use pool::{BufferPool, PooledBuffer};
+pub use reader::{BufferReader, Savepoint, Utf16Error};
+pub use records::ParseError;
+pub use tlv::TlvReader;
+pub use writer::{BufferWriter, InteriorNulError};
+#[cfg(feature = "z85")]
+pub use z85::Z85Error;
+
 /// Represents a Roblox `buffer`.
-#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
-pub struct Buffer(Vec<u8>);
+///
+/// Comparing buffers with `==` is not constant time, so it can leak the contents of secret buffers through timing.
+/// Use `Buffer::ct_eq` from the `subtle` feature for those instead.
+///
+/// Buffers own a `Vec<u8>` by default, but can be backed by anything that is `AsRef<[u8]>`, such as `Arc<[u8]>`
+/// or `&[u8]`, to serialize bytes without copying them. Deserializing and mutating need the default backing.
+///
+/// Formats that aren't human readable, such as MessagePack, store buffers as raw bytes instead of the Roblox envelope.
+///
+/// An empty buffer serializes with empty data (`"base64":""`), which deserializes back to an empty buffer.
+/// This is distinct from a missing buffer, which as an `Option<Buffer>` is `null`. Objects missing the
+/// envelope entirely, such as `{}`, are not buffers and fail to deserialize.
+///
+/// Buffers can remember the encoding they prefer to serialize with, set with [`Buffer::with_encoding`].
+/// This is only a hint for serializing, buffers with the same contents are equal whatever their encoding.
+/// They serialize as base64 by default, or with the `default-compress` feature, as whichever of base64 and zbase64
+/// is shorter. That saves space for compressible buffers, but compresses every buffer just to compare, and the
+/// feature applies to every use of `Buffer` in the dependency tree. It only changes the [`Serialize`] implementation,
+/// methods that take an encoding or options use exactly what they're given.
+#[derive(Debug)]
+pub struct Buffer<T = Vec<u8>>(T, BufferEncoding);
 
 impl Buffer {
     /// Creates a new buffer from bytes.
-    pub fn new(data: Vec<u8>) -> Self {
-        Self(data)
+    ///
+    /// ```
+    /// # use roblox_buffer::Buffer;
+    /// assert_eq!(Buffer::new(vec![104, 105]).as_ref(), b"hi");
+    /// assert_eq!(Buffer::new(&[104, 105][..]).as_ref(), b"hi");
+    /// assert_eq!(Buffer::new("hi").as_ref(), b"hi");
+    /// assert_eq!(Buffer::new(String::from("hi")).as_ref(), b"hi");
+    /// ```
+    pub fn new(data: impl Into<Vec<u8>>) -> Self {
+        Self(data.into(), DEFAULT_ENCODING)
+    }
+
+    /// Creates a buffer of `len` bytes, where each byte is the result of calling `f` with its index.
+    ///
+    /// ```
+    /// # use roblox_buffer::Buffer;
+    /// assert_eq!(Buffer::from_fn(3, |i| i as u8 * 2).as_ref(), [0, 2, 4]);
+    /// ```
+    pub fn from_fn(len: usize, f: impl FnMut(usize) -> u8) -> Self {
+        Self::new((0..len).map(f).collect::<Vec<u8>>())
+    }
+
+    /// Creates an empty buffer with at least the given capacity, returning an error instead of aborting if allocation fails.
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        let mut data = Vec::new();
+        data.try_reserve(capacity)?;
+        Ok(Self(data, DEFAULT_ENCODING))
+    }
+
+    /// Reserves capacity for at least `additional` more bytes, returning an error instead of aborting if allocation fails.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.0.try_reserve(additional)
+    }
+
+    /// Inserts a byte at the start of the buffer, shifting the rest of the contents along.
+    pub fn prepend(&mut self, byte: u8) {
+        self.0.insert(0, byte);
+    }
+
+    /// Inserts bytes at the start of the buffer, such as a header, shifting the rest of the contents along once.
+    pub fn prepend_slice(&mut self, bytes: &[u8]) {
+        self.0.splice(..0, bytes.iter().copied());
+    }
+
+    /// Pads the end of the buffer with zeros until it is `size` bytes long, doing nothing if it already is.
+    pub fn zero_pad_to(&mut self, size: usize) {
+        if self.0.len() < size {
+            self.0.resize(size, 0);
+        }
+    }
+
+    /// Resizes the buffer to `new_len` bytes, filling any new bytes by calling `f`, the same as [`Vec::resize_with`].
+    /// Shrinking the buffer truncates it without calling `f`.
+    pub fn resize_with(&mut self, new_len: usize, f: impl FnMut() -> u8) {
+        self.0.resize_with(new_len, f);
+    }
+
+    /// Returns the buffer in mutable chunks of `size` bytes, the last of which may be shorter, the same as
+    /// [`slice::chunks_mut`]. Useful for transforming blocks in place, such as decrypting them.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    pub fn chunks_mut(&mut self, size: usize) -> impl Iterator<Item = &mut [u8]> {
+        self.0.chunks_mut(size)
+    }
+
+    /// Returns the same chunks as [`Buffer::chunks_mut`] as a parallel iterator. Enabled with the `rayon` feature.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    #[cfg(feature = "rayon")]
+    pub fn par_chunks_mut(
+        &mut self,
+        size: usize,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = &mut [u8]> {
+        use rayon::slice::ParallelSliceMut;
+
+        self.0.par_chunks_mut(size)
+    }
+
+    /// Truncates the buffer to at most `max_bytes`, backing off so that a multi-byte UTF-8 character isn't cut in half.
+    ///
+    /// This assumes the contents are UTF-8. For other bytes, it backs off over at most 3 bytes that look like the
+    /// middle of a UTF-8 character, so the result is never shorter than `max_bytes - 3`.
+    pub fn truncate_to_utf8_boundary(&mut self, max_bytes: usize) {
+        if self.0.len() <= max_bytes {
+            return;
+        }
+
+        let mut len = max_bytes;
+        while len > 0 && max_bytes - len < 3 && self.0[len] & 0b1100_0000 == 0b1000_0000 {
+            len -= 1;
+        }
+
+        self.0.truncate(len);
+    }
+
+    /// Removes all zeros from the end of the buffer.
+    ///
+    /// This is lossy, zeros that were part of the data are removed the same as padding from [`Buffer::zero_pad_to`].
+    /// Only use it on data that can't end with a zero, or store the length separately.
+    pub fn trim_trailing_zeros(&mut self) {
+        let len = self
+            .0
+            .iter()
+            .rposition(|&byte| byte != 0)
+            .map_or(0, |last| last + 1);
+        self.0.truncate(len);
     }
 
     /// Returns the inner vector of the buffer.
@@ -23,90 +218,513 @@ impl Buffer {
     }
 }
 
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Creates a buffer backed by the given bytes.
+    ///
+    /// ```
+    /// # use roblox_buffer::Buffer;
+    /// # use std::sync::Arc;
+    /// let shared: Arc<[u8]> = Arc::from(&b"hi"[..]);
+    /// assert_eq!(Buffer::from_inner(shared).as_ref(), b"hi");
+    /// ```
+    pub fn from_inner(inner: T) -> Self {
+        Self(inner, DEFAULT_ENCODING)
+    }
+
+    /// Returns the bytes backing the buffer.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+
+    /// Returns a copy of the bytes of the buffer, keeping the buffer.
+    ///
+    /// ```
+    /// # use roblox_buffer::Buffer;
+    /// let buffer = Buffer::new("hi");
+    /// assert_eq!(buffer.to_vec(), b"hi");
+    /// assert_eq!(buffer.into_vec(), b"hi");
+    /// ```
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.0.as_ref().to_vec()
+    }
+
+    /// Returns whether the buffer begins with the given bytes, such as the magic number of a format.
+    pub fn starts_with(&self, prefix: &[u8]) -> bool {
+        self.0.as_ref().starts_with(prefix)
+    }
+
+    /// Returns a view of part of the buffer, which serializes as its own buffer without copying.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds, the same as slicing.
+    pub fn view(&self, range: impl RangeBounds<usize>) -> BufferSlice<'_> {
+        Buffer(
+            &self.0.as_ref()[(range.start_bound().cloned(), range.end_bound().cloned())],
+            self.1,
+        )
+    }
+
+    /// Returns the contents of the buffer as base64, without the Roblox envelope.
+    pub fn to_base64(&self) -> String {
+        BASE64.encode(self.0.as_ref())
+    }
+
+    /// Writes the contents of the buffer as base64, without the Roblox envelope.
+    /// Unlike [`Buffer::to_base64`], this encodes in small chunks instead of allocating the whole string.
+    pub fn write_base64<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+        // Multiple of 3 so that only the last chunk is padded.
+        const CHUNK_LEN: usize = 3 * 256;
+
+        let mut output = [0; CHUNK_LEN / 3 * 4];
+
+        for chunk in self.0.as_ref().chunks(CHUNK_LEN) {
+            let output = &mut output[..BASE64.encode_len(chunk.len())];
+            BASE64.encode_mut(chunk, output);
+            w.write_str(std::str::from_utf8(output).expect("base64 is ascii"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the first byte and the rest of the buffer, or `None` if it is empty.
+    pub fn split_first(&self) -> Option<(u8, &[u8])> {
+        self.0
+            .as_ref()
+            .split_first()
+            .map(|(&first, rest)| (first, rest))
+    }
+
+    /// Returns the last byte and the rest of the buffer, or `None` if it is empty.
+    pub fn split_last(&self) -> Option<(u8, &[u8])> {
+        self.0
+            .as_ref()
+            .split_last()
+            .map(|(&last, rest)| (last, rest))
+    }
+
+    /// Returns the slices of the buffer between each `delimiter`, the same as [`slice::split`].
+    pub fn split_on(&self, delimiter: u8) -> impl Iterator<Item = &[u8]> {
+        self.0.as_ref().split(move |&byte| byte == delimiter)
+    }
+
+    /// Returns every run of `size` bytes in the buffer, overlapping, the same as [`slice::windows`].
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    pub fn windows(&self, size: usize) -> impl Iterator<Item = &[u8]> {
+        self.0.as_ref().windows(size)
+    }
+
+    /// Splits the buffer into arrays of `N` bytes and the remainder, the same as [`slice::as_chunks`].
+    ///
+    /// # Panics
+    /// Panics if `N` is 0.
+    pub fn as_chunks<const N: usize>(&self) -> (&[[u8; N]], &[u8]) {
+        self.0.as_ref().as_chunks()
+    }
+
+    /// Splits the buffer into `n` owned parts whose lengths differ by at most one byte, longest first.
+    /// Returns fewer parts if the buffer is shorter than `n` bytes, so no part is empty, and none if `n` is 0.
+    pub fn split_into_parts(&self, n: usize) -> Vec<Buffer> {
+        let bytes = self.0.as_ref();
+        let n = n.min(bytes.len());
+        let mut parts = Vec::with_capacity(n);
+        let mut rest = bytes;
+
+        for index in 0..n {
+            let len = bytes.len() / n + (index < bytes.len() % n) as usize;
+            let (part, remaining) = rest.split_at(len);
+            parts.push(Buffer(part.to_vec(), self.1));
+            rest = remaining;
+        }
+
+        parts
+    }
+
+    /// Returns the contents of the buffer as a string, replacing invalid UTF-8 with `U+FFFD`, for logging and display.
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.0.as_ref())
+    }
+
+    /// Returns the encoding the buffer serializes with, set with [`Buffer::with_encoding`].
+    /// Defaults to [`BufferEncoding::Base64`], or [`BufferEncoding::Auto`] with the `default-compress` feature.
+    pub fn encoding(&self) -> BufferEncoding {
+        self.1
+    }
+
+    /// Sets the encoding the [`Serialize`] implementation uses, such as for buffers in structs deriving it.
+    pub fn with_encoding(self, encoding: BufferEncoding) -> Self {
+        Self(self.0, encoding)
+    }
+
+    /// Serializes the buffer using the given encoding.
+    /// The [`Serialize`] implementation is equivalent to calling this with [`Buffer::encoding`].
+    pub fn serialize_with_encoding<S>(
+        &self,
+        encoding: BufferEncoding,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        self.serialize_with_options(&EncodeOptions::new(encoding), serializer)
+    }
+
+    /// Serializes the buffer using the given options.
+    pub fn serialize_with_options<S>(
+        &self,
+        options: &EncodeOptions,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        encode::serialize_bytes(self.0.as_ref(), options, serializer)
+    }
+
+    /// Serializes the buffer compressed by `compress`, with the compressed data as base64 under `key`.
+    /// This is for experimenting with other compressors, Roblox itself only understands `base64` and `zbase64`.
+    pub fn serialize_with_compressor<F, S>(
+        &self,
+        key: &str,
+        compress: F,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        F: Fn(&[u8]) -> std::io::Result<Vec<u8>>,
+        S: serde::ser::Serializer,
+    {
+        let compressed = compress(self.0.as_ref()).map_err(serde::ser::Error::custom)?;
+        encode::serialize_precompressed(key, &compressed, serializer)
+    }
+
+    /// Returns the buffer as JSON in a canonical form, for hashing and comparing serialized buffers.
+    ///
+    /// This is always plain base64 with the keys in the same order and no whitespace,
+    /// and is guaranteed to stay byte for byte the same across versions of this crate.
+    pub fn to_canonical_json(&self) -> String {
+        let mut json = String::from(r#"{"m":null,"t":"buffer","base64":""#);
+        self.write_base64(&mut json)
+            .expect("writing to a string can't fail");
+        json.push_str(r#""}"#);
+        json
+    }
+
+    /// Returns the buffer as JSON that can be put in a Luau long string such as `[[...]]` or `[==[...]==]` as is.
+    ///
+    /// This is the same as [`Buffer::to_canonical_json`], which only ever contains ASCII letters, digits, and
+    /// `{}":,+/=`. It has no `]` to end the long string early and no newlines for the long string to drop.
+    pub fn to_luau_safe_json(&self) -> String {
+        let json = self.to_canonical_json();
+        debug_assert!(!json.contains([']', '\n', '\r']));
+        json
+    }
+
+    /// Returns Luau source that creates the buffer, such as `buffer.fromstring("hi\000")`.
+    ///
+    /// Printable ASCII is written as is, other than `"` and `\` which are escaped. Every other byte is written as a
+    /// three digit `\ddd` escape, so a digit after it is never read as part of the escape.
+    pub fn to_luau_literal(&self) -> String {
+        use std::fmt::Write;
+
+        let mut literal = String::from("buffer.fromstring(\"");
+        for &byte in self.0.as_ref() {
+            match byte {
+                b'"' => literal.push_str("\\\""),
+                b'\\' => literal.push_str("\\\\"),
+                b' '..=b'~' => literal.push(byte as char),
+                _ => write!(literal, "\\{byte:03}").expect("writing to a string can't fail"),
+            }
+        }
+        literal.push_str("\")");
+        literal
+    }
+
+    /// Compares the contents of two buffers in constant time, for secrets such as tokens or HMACs.
+    /// Only the contents are protected, buffers of different lengths are unequal immediately.
+    #[cfg(feature = "subtle")]
+    pub fn ct_eq<U: AsRef<[u8]>>(&self, other: &Buffer<U>) -> bool {
+        subtle::ConstantTimeEq::ct_eq(self.0.as_ref(), other.0.as_ref()).into()
+    }
+
+    /// Checks that the buffer, serialized as JSON with the given encoding, fits in a DataStore value.
+    /// Returns the length of the serialized JSON.
+    pub fn fits_datastore(&self, encoding: BufferEncoding) -> Result<usize, TooLargeError> {
+        let (key, data_len) = encode::encoded_len(self.0.as_ref(), &EncodeOptions::new(encoding))
+            .expect("compressing into memory should not fail");
+
+        // {"m":null,"t":"buffer","<key>":"<data>"}
+        let len = r#"{"m":null,"t":"buffer","":""}"#.len() + key.len() + data_len;
+
+        if len > DATASTORE_MAX_VALUE_BYTES {
+            return Err(TooLargeError {
+                len,
+                max: DATASTORE_MAX_VALUE_BYTES,
+            });
+        }
+
+        Ok(len)
+    }
+}
+
+/// The maximum size of a buffer in Roblox, 1 GiB.
+/// Compressed buffers that decompress to more than this are rejected.
+pub const MAX_BUFFER_LEN: usize = 1024 * 1024 * 1024;
+
+/// The maximum length of a DataStore value, in characters.
+pub const DATASTORE_MAX_VALUE_BYTES: usize = 4 * 1024 * 1024;
+
+/// The encoding buffers serialize with unless set with [`Buffer::with_encoding`].
+/// [`BufferEncoding::Base64`], or [`BufferEncoding::Auto`] with the `default-compress` feature.
+#[cfg(not(feature = "default-compress"))]
+const DEFAULT_ENCODING: BufferEncoding = BufferEncoding::Base64;
+#[cfg(feature = "default-compress")]
+const DEFAULT_ENCODING: BufferEncoding = BufferEncoding::Auto;
+
+/// The encoding used when serializing a [`Buffer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub enum BufferEncoding {
+    /// Plain base64, under the `base64` key.
+    #[default]
+    Base64,
+    /// zstd compressed and then base64 encoded, under the `zbase64` key.
+    ZBase64,
+    /// Whichever of [`BufferEncoding::Base64`] and [`BufferEncoding::ZBase64`] is shorter.
+    Auto,
+}
+
+/// The zstd window logs accepted by [`EncodeOptions::window_log`].
+///
+/// zstd decoders refuse frames with a window larger than `2^27` bytes unless explicitly configured to,
+/// which includes the deserializer in this crate. Roblox doesn't document its own limit, so anything
+/// above the zstd default should be assumed to not decode in Roblox.
+pub const WINDOW_LOG_RANGE: std::ops::RangeInclusive<u32> = 10..=27;
+
+/// Options for serializing a [`Buffer`], used by [`Buffer::serialize_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct EncodeOptions {
+    encoding: BufferEncoding,
+    window_log: Option<u32>,
+    long_distance_matching: bool,
+    include_len: bool,
+}
+
+impl EncodeOptions {
+    /// Creates options using the given encoding and the default compression settings.
+    pub fn new(encoding: BufferEncoding) -> Self {
+        Self {
+            encoding,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the zstd window log, the base 2 log of how far back compression can look for matches.
+    /// Must be within [`WINDOW_LOG_RANGE`] to keep the output decodable.
+    pub fn window_log(self, window_log: u32) -> Result<Self, InvalidWindowLog> {
+        if !WINDOW_LOG_RANGE.contains(&window_log) {
+            return Err(InvalidWindowLog(window_log));
+        }
+
+        Ok(Self {
+            window_log: Some(window_log),
+            ..self
+        })
+    }
+
+    /// Enables zstd long distance matching, which improves compression of large, repetitive buffers.
+    /// Unless a window log is set, this uses a window log of 27, the largest Roblox can be assumed to decode.
+    pub fn long_distance_matching(self, long_distance_matching: bool) -> Self {
+        Self {
+            long_distance_matching,
+            ..self
+        }
+    }
+
+    /// Sets whether to include a `len` field with the decoded length of the buffer, off by default.
+    /// Deserializing checks the length when the field is present, so consumers can validate buffers cheaply.
+    /// Roblox doesn't know about this field, so only use it for buffers that aren't read by Roblox.
+    pub fn include_len(self, include_len: bool) -> Self {
+        Self {
+            include_len,
+            ..self
+        }
+    }
+}
+
+/// Returned by [`EncodeOptions::window_log`] when the window log is outside of [`WINDOW_LOG_RANGE`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidWindowLog(pub u32);
+
+impl std::fmt::Display for InvalidWindowLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "window log {} is outside of the supported range {}..={}",
+            self.0,
+            WINDOW_LOG_RANGE.start(),
+            WINDOW_LOG_RANGE.end()
+        )
+    }
+}
+
+impl std::error::Error for InvalidWindowLog {}
+
+/// Returned by [`Buffer::fits_datastore`] when the serialized buffer is too large.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooLargeError {
+    /// The length of the serialized buffer.
+    pub len: usize,
+    /// The maximum allowed length.
+    pub max: usize,
+}
+
+impl std::fmt::Display for TooLargeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "serialized buffer is {} bytes, which is over the limit of {} bytes",
+            self.len, self.max
+        )
+    }
+}
+
+impl std::error::Error for TooLargeError {}
+
 impl<'de> Deserialize<'de> for Buffer {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "deserialize_buffer", level = "debug", skip_all)
+    )]
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        enum BufferData {
-            #[serde(rename = "base64")]
-            Base64(String),
-            #[serde(rename = "zbase64")]
-            ZBase64(String),
-        }
+        struct BytesVisitor;
 
-        #[derive(Deserialize)]
-        struct BufferInner {
-            t: String,
-            #[serde(flatten)]
-            data: BufferData,
-        }
-
-        let BufferInner { t, data } = BufferInner::deserialize(deserializer)?;
-
-        if t != "buffer" {
-            return Err(serde::de::Error::custom("expected buffer"));
-        }
-
-        let data = match data {
-            BufferData::Base64(base64) => BASE64
-                .decode(base64.as_bytes())
-                .map_err(serde::de::Error::custom)?,
-            BufferData::ZBase64(zbase64) => {
-                let compressed = BASE64
-                    .decode(zbase64.as_bytes())
-                    .map_err(serde::de::Error::custom)?;
-                let mut decoder = zstd::stream::Decoder::new(&compressed[..])
-                    .map_err(serde::de::Error::custom)?;
-                let mut data = Vec::new();
-                decoder
-                    .read_to_end(&mut data)
-                    .map_err(serde::de::Error::custom)?;
-                data
+        impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+            type Value = Buffer;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("bytes")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(Buffer::new(v))
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(Buffer::new(v))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(byte) = seq.next_element()? {
+                    data.push(byte);
+                }
+                Ok(Buffer::new(data))
             }
+        }
+
+        if !deserializer.is_human_readable() {
+            return deserializer.deserialize_byte_buf(BytesVisitor);
+        }
+
+        de::deserialize_envelope(deserializer, false)
+    }
+}
+
+/// Decompresses zstd frames, one after another, failing with [`BufferError::LimitExceeded`] inside the
+/// [`std::io::Error`] instead of decompressing more than `max` bytes.
+/// Allocation failures are returned as errors instead of aborting, since the size comes from untrusted input.
+fn decompress(compressed: &[u8], max: usize) -> std::io::Result<Vec<u8>> {
+    // Roblox always includes the content size, so the output can usually be allocated once up front.
+    let capacity = content_size(compressed).map_or(0, |size| size.min(max as u64) as usize);
+
+    read_to_end_limited(zstd::stream::Decoder::new(compressed)?, capacity, max)
+}
+
+/// Reads everything from a decoder, failing with [`BufferError::LimitExceeded`] inside the [`std::io::Error`]
+/// instead of reading more than `max` bytes. Starts with room for `capacity` bytes.
+/// Allocation failures are returned as errors instead of aborting, since the size comes from untrusted input.
+pub(crate) fn read_to_end_limited(
+    mut decoder: impl Read,
+    capacity: usize,
+    max: usize,
+) -> std::io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    data.try_reserve_exact(capacity)
+        .map_err(std::io::Error::other)?;
+
+    let mut chunk = [0; 32 * 1024];
+
+    loop {
+        let read = match decoder.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
         };
 
-        Ok(Self(data))
+        if data.len() + read > max {
+            return Err(std::io::Error::other(BufferError::LimitExceeded { max }));
+        }
+
+        data.try_reserve(read).map_err(std::io::Error::other)?;
+        data.extend_from_slice(&chunk[..read]);
+    }
+
+    Ok(data)
+}
+
+/// Returns the total content size of the zstd frames, which the decoder reads one after another,
+/// or `None` if any frame doesn't include its content size.
+fn content_size(mut frames: &[u8]) -> Option<u64> {
+    let mut size = 0u64;
+
+    while !frames.is_empty() {
+        size = size.checked_add(zstd::zstd_safe::get_frame_content_size(frames).ok()??)?;
+        frames = &frames[zstd::zstd_safe::find_frame_compressed_size(frames).ok()?..];
     }
+
+    Some(size)
 }
 
-impl Serialize for Buffer {
+impl<T: AsRef<[u8]>> Serialize for Buffer<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::ser::Serializer,
     {
-        use serde::ser::SerializeMap;
-
-        let mut map = serializer.serialize_map(Some(3))?;
-
-        map.serialize_entry("m", &())?; // "m": null
-        map.serialize_entry("t", "buffer")?;
-
-        // This DOESN'T deserialize in Roblox.
-        // I couldn't figure out why.
-        if false {
-            let base64 = BASE64.encode(&self.0);
-
-            let mut compressed: Vec<u8> = Vec::new();
-            let mut encoder = zstd::stream::Encoder::new(&mut compressed, 0).unwrap();
-            encoder
-                .set_pledged_src_size(Some(self.0.len() as u64))
-                .unwrap();
-            encoder.include_contentsize(true).unwrap();
-            encoder.write_all(&self.0).unwrap();
-            encoder.finish().unwrap();
-
-            if compressed.len() < base64.len() {
-                map.serialize_entry("zbase64", &BASE64.encode(&compressed))?;
-            } else {
-                map.serialize_entry("base64", &base64)?;
-            }
-        }
+        self.serialize_with_encoding(self.1, serializer)
+    }
+}
 
-        map.serialize_entry("base64", &BASE64.encode(&self.0))?;
+impl<T: Clone> Clone for Buffer<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), self.1)
+    }
+
+    // Reuses the existing allocation where the backing supports it, such as `Vec<u8>`.
+    fn clone_from(&mut self, source: &Self) {
+        self.0.clone_from(&source.0);
+        self.1 = source.1;
+    }
+}
+
+impl<T: AsRef<[u8]>, U: AsRef<[u8]>> PartialEq<Buffer<U>> for Buffer<T> {
+    fn eq(&self, other: &Buffer<U>) -> bool {
+        self.0.as_ref() == other.0.as_ref()
+    }
+}
+
+impl<T: AsRef<[u8]>> Eq for Buffer<T> {}
 
-        map.end()
+// Only the contents are hashed, the same as `[u8]`, so that `Borrow<[u8]>` is correct.
+impl<T: AsRef<[u8]>> std::hash::Hash for Buffer<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.as_ref().hash(state);
     }
 }
 
@@ -116,12 +734,43 @@ impl From<Buffer> for Vec<u8> {
     }
 }
 
-impl AsRef<[u8]> for Buffer {
-    fn as_ref(&self) -> &[u8] {
+impl From<Buffer> for Box<[u8]> {
+    fn from(value: Buffer) -> Self {
+        value.0.into_boxed_slice()
+    }
+}
+
+// Vec<u8> hashes and compares the same as [u8], so this is consistent with the derived impls.
+impl std::borrow::Borrow<[u8]> for Buffer {
+    fn borrow(&self) -> &[u8] {
         &self.0
     }
 }
 
+impl<T: AsRef<[u8]>> AsRef<[u8]> for Buffer<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+/// A borrowed view of part of a buffer, created with [`Buffer::view`].
+pub type BufferSlice<'a> = Buffer<&'a [u8]>;
+
+impl<'a> BufferSlice<'a> {
+    /// Creates a view of `len` bytes at `offset` in `data`, or `None` if that is out of bounds.
+    pub fn from_parts(data: &'a [u8], offset: usize, len: usize) -> Option<Self> {
+        let range = access::checked_range(offset, len, data.len()).ok()?;
+        Some(Self(&data[range], DEFAULT_ENCODING))
+    }
+}
+
+// Only for the default backing, so that `Buffer::default()` doesn't need a type annotation.
+impl Default for Buffer {
+    fn default() -> Self {
+        Self(Vec::new(), DEFAULT_ENCODING)
+    }
+}
+
 impl AsMut<[u8]> for Buffer {
     fn as_mut(&mut self) -> &mut [u8] {
         &mut self.0
@@ -130,7 +779,7 @@ impl AsMut<[u8]> for Buffer {
 
 impl FromIterator<u8> for Buffer {
     fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
-        Self(Vec::from_iter(iter))
+        Self::new(Vec::from_iter(iter))
     }
 }
 
@@ -140,6 +789,97 @@ impl Extend<u8> for Buffer {
     }
 }
 
+impl<'a> Extend<&'a u8> for Buffer {
+    fn extend<T: IntoIterator<Item = &'a u8>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+/// Appends the contents of each buffer.
+impl<U: AsRef<[u8]>> Extend<Buffer<U>> for Buffer {
+    fn extend<T: IntoIterator<Item = Buffer<U>>>(&mut self, iter: T) {
+        for buffer in iter {
+            self.0.extend_from_slice(buffer.0.as_ref());
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Buffer {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        // Occasionally repeat a pattern, so that compressible buffers show up too.
+        if u.ratio(1, 4)? {
+            let pattern = Vec::<u8>::arbitrary(u)?;
+            let count = u.int_in_range(1..=64)?;
+            return Ok(Self::new(pattern.repeat(count)));
+        }
+
+        Ok(Self::new(Vec::arbitrary(u)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        arbitrary::size_hint::and(bool::size_hint(depth), Vec::<u8>::size_hint(depth))
+    }
+}
+
+/// Encodes the raw bytes, length prefixed, instead of the Roblox envelope.
+#[cfg(feature = "bincode")]
+impl<T: AsRef<[u8]>> bincode::Encode for Buffer<T> {
+    fn encode<E: bincode::enc::Encoder>(
+        &self,
+        encoder: &mut E,
+    ) -> Result<(), bincode::error::EncodeError> {
+        self.0.as_ref().encode(encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<Context> bincode::Decode<Context> for Buffer {
+    fn decode<D: bincode::de::Decoder<Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, bincode::error::DecodeError> {
+        Vec::<u8>::decode(decoder).map(Self::new)
+    }
+}
+
+#[cfg(feature = "bincode")]
+bincode::impl_borrow_decode!(Buffer);
+
+// Only the envelope Roblox writes, not everything that can be deserialized, such as `data` with `compression`.
+#[cfg(feature = "schemars")]
+impl<T> schemars::JsonSchema for Buffer<T> {
+    fn schema_name() -> Cow<'static, str> {
+        "Buffer".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "A Roblox buffer.",
+            "type": "object",
+            "properties": {
+                "m": { "type": "null" },
+                "t": { "const": "buffer" },
+                "base64": {
+                    "description": "The contents, as base64.",
+                    "type": "string",
+                    "contentEncoding": "base64",
+                },
+                "zbase64": {
+                    "description": "The contents compressed as a zstd frame, as base64.",
+                    "type": "string",
+                    "contentEncoding": "base64",
+                },
+                "len": { "type": "integer", "minimum": 0 },
+            },
+            "required": ["t"],
+            "oneOf": [
+                { "required": ["base64"] },
+                { "required": ["zbase64"] },
+            ],
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,4 +902,1071 @@ mod tests {
             Buffer::new(b"hello hello hello hello hello hello hello hello hello hello hello hello hello hello hello hello".to_vec())
         )
     }
+
+    #[test]
+    fn test_from_reader() {
+        // Readers can't lend out their strings, so the data is owned instead of borrowed.
+        let base64 = std::io::Cursor::new(r#"{"m":null,"t":"buffer","base64":"aGVsbG8gd29ybGQ="}"#);
+        assert_eq!(
+            serde_json::from_reader::<_, Buffer>(base64).unwrap(),
+            Buffer::new("hello world")
+        );
+
+        let zbase64 = std::io::Cursor::new(
+            r#"{"m":null,"t":"buffer","zbase64":"KLUv/SBfbQAAMGhlbGxvIAEAlqkUAQ=="}"#,
+        );
+        assert_eq!(
+            serde_json::from_reader::<_, Buffer>(zbase64).unwrap(),
+            Buffer::new("hello ".repeat(16).trim_end())
+        );
+
+        let invalid = std::io::Cursor::new(r#"{"m":null,"t":"buffer","zbase64":"aGk="}"#);
+        assert!(serde_json::from_reader::<_, Buffer>(invalid).is_err());
+    }
+
+    /// Deterministic xorshift noise, for incompressible data. Shared with the tests of other modules.
+    pub(crate) fn random_bytes(len: usize) -> Vec<u8> {
+        let mut state = 0x2545f4914f6cdd1d_u64;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    fn to_json(buffer: &Buffer, encoding: BufferEncoding) -> String {
+        to_json_with_options(buffer, &EncodeOptions::new(encoding))
+    }
+
+    fn to_json_with_options(buffer: &Buffer, options: &EncodeOptions) -> String {
+        let mut json = Vec::new();
+        buffer
+            .serialize_with_options(options, &mut serde_json::Serializer::new(&mut json))
+            .unwrap();
+        String::from_utf8(json).unwrap()
+    }
+
+    #[test]
+    fn test_base64_ser() {
+        assert_eq!(
+            serde_json::to_string(&Buffer::new(b"hello world".to_vec())).unwrap(),
+            r#"{"m":null,"t":"buffer","base64":"aGVsbG8gd29ybGQ="}"#
+        );
+    }
+
+    #[test]
+    fn test_zbase64_ser() {
+        let buffer = Buffer::new(b"hello hello hello hello hello hello hello hello hello hello hello hello hello hello hello hello".to_vec());
+
+        assert_eq!(
+            to_json(&buffer, BufferEncoding::ZBase64),
+            r#"{"m":null,"t":"buffer","zbase64":"KLUv/SBfbQAAMGhlbGxvIAEAlqkUAQ=="}"#
+        );
+
+        assert_eq!(
+            to_json(&buffer, BufferEncoding::Auto),
+            to_json(&buffer, BufferEncoding::ZBase64)
+        );
+
+        assert_eq!(
+            serde_json::from_str::<Buffer>(&to_json(&buffer, BufferEncoding::ZBase64)).unwrap(),
+            buffer
+        );
+    }
+
+    #[test]
+    fn test_auto_ser_incompressible() {
+        let buffer = Buffer::new(b"hello world".to_vec());
+
+        assert_eq!(
+            to_json(&buffer, BufferEncoding::Auto),
+            to_json(&buffer, BufferEncoding::Base64)
+        );
+    }
+
+    #[test]
+    fn test_fits_datastore() {
+        for encoding in [
+            BufferEncoding::Base64,
+            BufferEncoding::ZBase64,
+            BufferEncoding::Auto,
+        ] {
+            let buffer = Buffer::new(b"hello world".to_vec());
+            assert_eq!(
+                buffer.fits_datastore(encoding),
+                Ok(to_json(&buffer, encoding).len())
+            );
+        }
+    }
+
+    #[test]
+    fn test_fits_datastore_boundary() {
+        // Every 3 bytes become 4 base64 characters, so this is the largest buffer that fits.
+        let envelope_len = r#"{"m":null,"t":"buffer","base64":""}"#.len();
+        let max_len = (DATASTORE_MAX_VALUE_BYTES - envelope_len) / 4 * 3;
+
+        let buffer = Buffer::new(vec![0; max_len]);
+        let len = buffer.fits_datastore(BufferEncoding::Base64).unwrap();
+        assert_eq!(len, to_json(&buffer, BufferEncoding::Base64).len());
+        assert!(len <= DATASTORE_MAX_VALUE_BYTES);
+
+        let buffer = Buffer::new(vec![0; max_len + 1]);
+        assert_eq!(
+            buffer.fits_datastore(BufferEncoding::Base64),
+            Err(TooLargeError {
+                len: to_json(&buffer, BufferEncoding::Base64).len(),
+                max: DATASTORE_MAX_VALUE_BYTES,
+            })
+        );
+
+        // The same data compresses well enough to fit.
+        assert!(buffer.fits_datastore(BufferEncoding::ZBase64).is_ok());
+    }
+
+    #[test]
+    fn test_window_log_range() {
+        assert_eq!(
+            EncodeOptions::new(BufferEncoding::ZBase64).window_log(9),
+            Err(InvalidWindowLog(9))
+        );
+        assert_eq!(
+            EncodeOptions::new(BufferEncoding::ZBase64).window_log(28),
+            Err(InvalidWindowLog(28))
+        );
+        assert!(
+            EncodeOptions::new(BufferEncoding::ZBase64)
+                .window_log(27)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_long_distance_matching() {
+        // A random block repeated further apart than the default window can see.
+        let block = random_bytes(4 * 1024 * 1024);
+        let buffer = Buffer::new(block.repeat(2));
+
+        let options = EncodeOptions::new(BufferEncoding::ZBase64);
+        let short = to_json_with_options(&buffer, &options);
+        let long = to_json_with_options(&buffer, &options.long_distance_matching(true));
+
+        assert!(long.len() < short.len() * 3 / 4);
+        assert_eq!(serde_json::from_str::<Buffer>(&long).unwrap(), buffer);
+    }
+
+    #[test]
+    fn test_split_first_last() {
+        let buffer = Buffer::new(vec![1, 2, 3]);
+        assert_eq!(buffer.split_first(), Some((1, &[2, 3][..])));
+        assert_eq!(buffer.split_last(), Some((3, &[1, 2][..])));
+
+        let buffer = Buffer::default();
+        assert_eq!(buffer.split_first(), None);
+        assert_eq!(buffer.split_last(), None);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_round_trip() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let seed = random_bytes(64 * 1024);
+
+        let mut u = Unstructured::new(&seed);
+        while !u.is_empty() {
+            let buffer = Buffer::arbitrary(&mut u).unwrap();
+
+            for encoding in [BufferEncoding::Base64, BufferEncoding::ZBase64] {
+                assert_eq!(
+                    serde_json::from_str::<Buffer>(&to_json(&buffer, encoding)).unwrap(),
+                    buffer
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_de_malformed_does_not_panic() {
+        let samples = [
+            r#"{"m":null,"t":"buffer","base64":"aGVsbG8gd29ybGQ="}"#,
+            r#"{"m":null,"t":"buffer","zbase64":"KLUv/SBfbQAAMGhlbGxvIAEAlqkUAQ=="}"#,
+        ];
+
+        let noise = random_bytes(4096);
+        let mut noise = noise.iter().copied().cycle();
+
+        for sample in samples {
+            for _ in 0..512 {
+                let mut input = sample.as_bytes().to_vec();
+                let position = noise.next().unwrap() as usize % input.len();
+
+                match noise.next().unwrap() % 3 {
+                    0 => input.truncate(position),
+                    1 => input[position] = noise.next().unwrap(),
+                    _ => input.insert(position, noise.next().unwrap()),
+                }
+
+                if let Ok(input) = std::str::from_utf8(&input) {
+                    let _ = serde_json::from_str::<Buffer>(input);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_base64() {
+        let mut output = String::from("base64: ");
+        Buffer::new("hello world")
+            .write_base64(&mut output)
+            .unwrap();
+        output.push_str(", ");
+        Buffer::new("hi").write_base64(&mut output).unwrap();
+
+        assert_eq!(output, "base64: aGVsbG8gd29ybGQ=, aGk=");
+    }
+
+    #[test]
+    fn test_write_base64_chunks() {
+        for len in [0, 767, 768, 769, 5000] {
+            let buffer = Buffer::new(random_bytes(len));
+            let mut output = String::new();
+            buffer.write_base64(&mut output).unwrap();
+            assert_eq!(output, buffer.to_base64());
+        }
+    }
+
+    #[test]
+    fn test_compression_de() {
+        assert_eq!(
+            serde_json::from_str::<Buffer>(
+                r#"{"t":"buffer","compression":"none","data":"aGVsbG8gd29ybGQ="}"#
+            )
+            .unwrap(),
+            Buffer::new("hello world")
+        );
+
+        assert_eq!(
+            serde_json::from_str::<Buffer>(
+                r#"{"t":"buffer","compression":"zstd","data":"KLUv/SBfbQAAMGhlbGxvIAEAlqkUAQ=="}"#
+            )
+            .unwrap(),
+            Buffer::new(
+                "hello hello hello hello hello hello hello hello hello hello hello hello hello hello hello hello"
+            )
+        );
+
+        assert_eq!(
+            serde_json::from_str::<Buffer>(r#"{"t":"buffer","data":"aGVsbG8gd29ybGQ="}"#).unwrap(),
+            Buffer::new("hello world")
+        );
+
+        assert!(
+            serde_json::from_str::<Buffer>(
+                r#"{"t":"buffer","compression":"gzip","data":"aGVsbG8gd29ybGQ="}"#
+            )
+            .is_err()
+        );
+
+        assert!(
+            serde_json::from_str::<Buffer>(
+                r#"{"t":"buffer","compression":"zstd","base64":"aGVsbG8gd29ybGQ="}"#
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_into_boxed_slice() {
+        let boxed: Box<[u8]> = Buffer::new("hi").into();
+        assert_eq!(&*boxed, b"hi");
+    }
+
+    #[test]
+    fn test_borrow_as_map_key() {
+        let mut map = std::collections::HashMap::new();
+        map.insert(Buffer::new("hello"), 1);
+        map.insert(Buffer::new("world"), 2);
+
+        assert_eq!(map.get(&b"hello"[..]), Some(&1));
+        assert_eq!(map.get(&b"world"[..]), Some(&2));
+        assert_eq!(map.get(&b"other"[..]), None);
+    }
+
+    #[test]
+    fn test_de_matches_naive() {
+        for len in [0, 1, 2, 3, 100, 4096, 100_000] {
+            let buffer = Buffer::new(random_bytes(len / 2).repeat(2));
+
+            for encoding in [BufferEncoding::Base64, BufferEncoding::ZBase64] {
+                let json = to_json(&buffer, encoding);
+
+                let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+                let naive = match value.get("base64") {
+                    Some(base64) => BASE64.decode(base64.as_str().unwrap().as_bytes()).unwrap(),
+                    None => zstd::stream::decode_all(
+                        &BASE64
+                            .decode(value["zbase64"].as_str().unwrap().as_bytes())
+                            .unwrap()[..],
+                    )
+                    .unwrap(),
+                };
+
+                assert_eq!(
+                    serde_json::from_str::<Buffer>(&json).unwrap().into_vec(),
+                    naive
+                );
+
+                // Not borrowed, since the reader can't lend out its strings.
+                assert_eq!(
+                    serde_json::from_reader::<_, Buffer>(json.as_bytes())
+                        .unwrap()
+                        .into_vec(),
+                    naive
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_reserve() {
+        let mut buffer = Buffer::try_with_capacity(16).unwrap();
+        assert!(buffer.0.capacity() >= 16);
+
+        buffer.extend(*b"hello");
+        buffer.try_reserve(32).unwrap();
+        assert!(buffer.0.capacity() >= 37);
+
+        assert!(buffer.try_reserve(usize::MAX).is_err());
+        assert!(Buffer::try_with_capacity(usize::MAX).is_err());
+        assert_eq!(buffer, Buffer::new("hello"));
+    }
+
+    #[test]
+    fn test_decompress_multiple_chunks() {
+        let buffer = Buffer::new(random_bytes(1000).repeat(100));
+        assert_eq!(
+            serde_json::from_str::<Buffer>(&to_json(&buffer, BufferEncoding::ZBase64)).unwrap(),
+            buffer
+        );
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        assert_eq!(
+            Buffer::new("hello world").to_canonical_json(),
+            r#"{"m":null,"t":"buffer","base64":"aGVsbG8gd29ybGQ="}"#
+        );
+        assert_eq!(
+            Buffer::default().to_canonical_json(),
+            r#"{"m":null,"t":"buffer","base64":""}"#
+        );
+    }
+
+    #[cfg(feature = "subtle")]
+    #[test]
+    fn test_ct_eq() {
+        assert!(Buffer::new("secret").ct_eq(&Buffer::new("secret")));
+        assert!(!Buffer::new("secret").ct_eq(&Buffer::new("secreT")));
+        assert!(!Buffer::new("secret").ct_eq(&Buffer::new("secrets")));
+        assert!(Buffer::default().ct_eq(&Buffer::default()));
+    }
+
+    #[test]
+    fn test_generic_backing_ser() {
+        let expected = r#"{"m":null,"t":"buffer","base64":"aGVsbG8gd29ybGQ="}"#;
+
+        let shared: std::sync::Arc<[u8]> = std::sync::Arc::from(&b"hello world"[..]);
+        let buffer = Buffer::from_inner(shared);
+        assert_eq!(serde_json::to_string(&buffer).unwrap(), expected);
+        assert_eq!(buffer.read_u8(0), Ok(b'h'));
+
+        let borrowed = Buffer::from_inner(&b"hello world"[..]);
+        assert_eq!(serde_json::to_string(&borrowed).unwrap(), expected);
+        assert_eq!(borrowed.to_base64(), "aGVsbG8gd29ybGQ=");
+        assert_eq!(borrowed.into_inner(), b"hello world");
+    }
+
+    #[test]
+    fn test_view_ser() {
+        let buffer = Buffer::new("hello world");
+
+        for (view, owned) in [
+            (buffer.view(6..), Buffer::new("world")),
+            (buffer.view(..5), Buffer::new("hello")),
+            (buffer.view(2..=3), Buffer::new("ll")),
+            (buffer.view(3..3), Buffer::default()),
+            (buffer.view(..), buffer.clone()),
+        ] {
+            assert_eq!(view.as_ref(), owned.as_ref());
+            assert_eq!(
+                serde_json::to_string(&view).unwrap(),
+                serde_json::to_string(&owned).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Save {
+            data: Option<Buffer>,
+        }
+
+        assert_eq!(
+            serde_json::from_str::<Save>(r#"{"data":{"m":null,"t":"buffer","base64":""}}"#)
+                .unwrap(),
+            Save {
+                data: Some(Buffer::default())
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<Save>(r#"{"data":null}"#).unwrap(),
+            Save { data: None }
+        );
+        assert!(serde_json::from_str::<Save>(r#"{"data":{}}"#).is_err());
+
+        assert_eq!(
+            serde_json::to_string(&Buffer::default()).unwrap(),
+            r#"{"m":null,"t":"buffer","base64":""}"#
+        );
+    }
+
+    #[test]
+    fn test_serialize_with_compressor() {
+        fn serialize_with_compressor(
+            buffer: &Buffer,
+            key: &str,
+            compress: impl Fn(&[u8]) -> std::io::Result<Vec<u8>>,
+        ) -> serde_json::Result<String> {
+            let mut json = Vec::new();
+            buffer.serialize_with_compressor(
+                key,
+                compress,
+                &mut serde_json::Serializer::new(&mut json),
+            )?;
+            Ok(String::from_utf8(json).unwrap())
+        }
+
+        // Pairs of (count, byte).
+        fn rle(data: &[u8]) -> std::io::Result<Vec<u8>> {
+            let mut output = Vec::new();
+            for run in data.chunk_by(|a, b| a == b) {
+                for chunk in run.chunks(255) {
+                    output.extend([chunk.len() as u8, chunk[0]]);
+                }
+            }
+            Ok(output)
+        }
+
+        let buffer = Buffer::new("aaaab");
+
+        assert_eq!(
+            serialize_with_compressor(&buffer, "base64", |data| Ok(data.to_vec())).unwrap(),
+            to_json(&buffer, BufferEncoding::Base64)
+        );
+
+        assert_eq!(
+            serialize_with_compressor(&buffer, "rle", rle).unwrap(),
+            format!(
+                r#"{{"m":null,"t":"buffer","rle":"{}"}}"#,
+                BASE64.encode(&[4, b'a', 1, b'b'])
+            )
+        );
+
+        assert!(
+            serialize_with_compressor(&buffer, "rle", |_| Err(std::io::Error::other("nope")))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_split_on() {
+        let buffer = Buffer::new("a\nbc\n\nd");
+        let lines: Vec<&[u8]> = buffer.split_on(b'\n').collect();
+        assert_eq!(lines, [&b"a"[..], b"bc", b"", b"d"]);
+
+        let buffer = Buffer::new("a\nbc\n");
+        let lines: Vec<&[u8]> = buffer.split_on(b'\n').collect();
+        assert_eq!(lines, [&b"a"[..], b"bc", b""]);
+
+        assert_eq!(Buffer::default().split_on(b'\n').count(), 1);
+    }
+
+    #[test]
+    fn test_messagepack() {
+        let buffer = Buffer::new("hello");
+
+        let packed = rmp_serde::to_vec(&buffer).unwrap();
+        assert_eq!(packed, b"\xc4\x05hello");
+        assert_eq!(rmp_serde::from_slice::<Buffer>(&packed).unwrap(), buffer);
+
+        let mut packed = Vec::new();
+        buffer
+            .serialize_with_encoding(
+                BufferEncoding::ZBase64,
+                &mut rmp_serde::Serializer::new(&mut packed),
+            )
+            .unwrap();
+        assert_eq!(packed, b"\xc4\x05hello");
+
+        // Still the envelope when MessagePack is configured to be human readable.
+        let mut packed = Vec::new();
+        buffer
+            .serialize(&mut rmp_serde::Serializer::new(&mut packed).with_human_readable())
+            .unwrap();
+        assert_ne!(packed, b"\xc4\x05hello");
+        assert_eq!(
+            Buffer::deserialize(
+                &mut rmp_serde::Deserializer::new(&packed[..]).with_human_readable()
+            )
+            .unwrap(),
+            buffer
+        );
+    }
+
+    #[test]
+    fn test_zero_pad() {
+        let mut buffer = Buffer::new(vec![1, 0, 2]);
+
+        buffer.zero_pad_to(16);
+        assert_eq!(buffer.as_ref().len(), 16);
+        assert_eq!(buffer.as_ref()[..4], [1, 0, 2, 0]);
+
+        buffer.zero_pad_to(4);
+        assert_eq!(buffer.as_ref().len(), 16);
+
+        buffer.trim_trailing_zeros();
+        assert_eq!(buffer, Buffer::new(vec![1, 0, 2]));
+
+        // Lossy for data ending in zeros.
+        let mut buffer = Buffer::new(vec![0, 0]);
+        buffer.trim_trailing_zeros();
+        assert_eq!(buffer, Buffer::default());
+    }
+
+    #[test]
+    fn test_with_encoding() {
+        #[derive(Serialize)]
+        struct Save {
+            data: Buffer,
+        }
+
+        let data = Buffer::new(vec![0; 100]);
+        let save = Save {
+            data: data.clone().with_encoding(BufferEncoding::ZBase64),
+        };
+        assert_eq!(save.data.encoding(), BufferEncoding::ZBase64);
+
+        let json = serde_json::to_string(&save).unwrap();
+        assert_eq!(
+            json,
+            format!(r#"{{"data":{}}}"#, to_json(&data, BufferEncoding::ZBase64))
+        );
+
+        // Views keep the encoding of the buffer they're from.
+        assert_eq!(save.data.view(..).encoding(), BufferEncoding::ZBase64);
+
+        // The encoding isn't part of the contents.
+        assert_eq!(data.encoding(), DEFAULT_ENCODING);
+        assert_eq!(save.data, data);
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(data);
+        assert!(set.contains(&save.data));
+        assert!(set.contains(&[0; 100][..]));
+    }
+
+    #[test]
+    fn test_from_fn() {
+        let ramp = Buffer::from_fn(300, |i| (i % 256) as u8);
+        assert_eq!(ramp.as_ref().len(), 300);
+        assert_eq!(ramp.as_ref()[0], 0);
+        assert_eq!(ramp.as_ref()[255], 255);
+        assert_eq!(ramp.as_ref()[256], 0);
+        assert_eq!(ramp.as_ref()[299], 43);
+
+        assert_eq!(Buffer::from_fn(0, |_| unreachable!()), Buffer::default());
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut buffer = Buffer::new("he");
+        buffer.extend(b"llo".iter());
+        assert_eq!(buffer, Buffer::new("hello"));
+
+        buffer.extend(vec![
+            Buffer::new(" "),
+            Buffer::default(),
+            Buffer::new("world"),
+        ]);
+        assert_eq!(buffer, Buffer::new("hello world"));
+
+        let other = Buffer::new("!?");
+        buffer.extend([other.view(..1)]);
+        assert_eq!(buffer, Buffer::new("hello world!"));
+    }
+
+    #[test]
+    fn test_len_field() {
+        let options = EncodeOptions::new(BufferEncoding::Base64).include_len(true);
+        let json = to_json_with_options(&Buffer::new("hello"), &options);
+        assert_eq!(
+            json,
+            r#"{"m":null,"t":"buffer","len":5,"base64":"aGVsbG8="}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Buffer>(&json).unwrap(),
+            Buffer::new("hello")
+        );
+
+        let options = EncodeOptions::new(BufferEncoding::ZBase64).include_len(true);
+        let json = to_json_with_options(&Buffer::new(vec![0; 100]), &options);
+        assert!(json.starts_with(r#"{"m":null,"t":"buffer","len":100,"zbase64":"#));
+        assert_eq!(
+            serde_json::from_str::<Buffer>(&json).unwrap(),
+            Buffer::new(vec![0; 100])
+        );
+
+        for (mismatched, expected) in [
+            (r#"{"m":null,"t":"buffer","len":4,"base64":"aGVsbG8="}"#, 4),
+            (r#"{"m":null,"t":"buffer","base64":"aGVsbG8=","len":6}"#, 6),
+        ] {
+            let error = BufferError::LengthMismatch {
+                expected,
+                actual: 5,
+            };
+            assert!(
+                serde_json::from_str::<Buffer>(mismatched)
+                    .unwrap_err()
+                    .to_string()
+                    .contains(&error.to_string()),
+                "{mismatched}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_string_lossy() {
+        let buffer = Buffer::new("hello");
+        assert!(matches!(buffer.to_string_lossy(), Cow::Borrowed("hello")));
+
+        let buffer = Buffer::new(b"hi\xffthere".to_vec());
+        assert_eq!(buffer.to_string_lossy(), "hi\u{fffd}there");
+    }
+
+    #[test]
+    fn test_clone_from() {
+        let mut buffer = Buffer::new(vec![0; 100]);
+        let capacity = buffer.0.capacity();
+        let pointer = buffer.0.as_ptr();
+
+        let source = Buffer::new("hello").with_encoding(BufferEncoding::ZBase64);
+        buffer.clone_from(&source);
+
+        assert_eq!(buffer, source);
+        assert_eq!(buffer.encoding(), BufferEncoding::ZBase64);
+        assert_eq!(buffer.0.capacity(), capacity);
+        assert_eq!(buffer.0.as_ptr(), pointer);
+    }
+
+    #[test]
+    fn test_zbase64_multiple_frames() {
+        let options = EncodeOptions::new(BufferEncoding::ZBase64);
+        let mut frames = encode::compress_into(b"hello ", &options, Vec::new()).unwrap();
+        frames = encode::compress_into(&random_bytes(100_000), &options, frames).unwrap();
+        frames = encode::compress_into(b"", &options, frames).unwrap();
+        frames = encode::compress_into(b" world", &options, frames).unwrap();
+
+        let json = format!(
+            r#"{{"m":null,"t":"buffer","zbase64":"{}"}}"#,
+            BASE64.encode(&frames)
+        );
+
+        let mut expected = b"hello ".to_vec();
+        expected.extend(random_bytes(100_000));
+        expected.extend(b" world");
+
+        assert_eq!(content_size(&frames), Some(expected.len() as u64));
+        assert_eq!(serde_json::from_str::<Buffer>(&json).unwrap().0, expected);
+
+        // Trailing bytes that aren't a frame.
+        frames.extend(b"oops");
+        assert_eq!(content_size(&frames), None);
+        assert!(decompress(&frames, MAX_BUFFER_LEN).is_err());
+    }
+
+    #[cfg(feature = "default-compress")]
+    #[test]
+    fn test_default_compress() {
+        let repetitive = Buffer::new("hello ".repeat(1000));
+        assert_eq!(repetitive.encoding(), BufferEncoding::Auto);
+        assert!(
+            serde_json::to_string(&repetitive)
+                .unwrap()
+                .starts_with(r#"{"m":null,"t":"buffer","zbase64":"#)
+        );
+
+        let incompressible = Buffer::new(random_bytes(1000));
+        assert!(
+            serde_json::to_string(&incompressible)
+                .unwrap()
+                .starts_with(r#"{"m":null,"t":"buffer","base64":"#)
+        );
+
+        let json = to_json(&repetitive, BufferEncoding::Base64);
+        assert!(json.starts_with(r#"{"m":null,"t":"buffer","base64":"#));
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        for buffer in [
+            Buffer::new("hello world"),
+            Buffer::default(),
+            Buffer::new(vec![0; 100]).with_encoding(BufferEncoding::ZBase64),
+        ] {
+            let yaml = serde_yaml::to_string(&buffer).unwrap();
+            assert!(yaml.starts_with("m: null\nt: buffer\n"), "{yaml}");
+            assert_eq!(serde_yaml::from_str::<Buffer>(&yaml).unwrap(), buffer);
+        }
+
+        // Other ways of writing null in YAML.
+        for yaml in [
+            "m: ~\nt: buffer\nbase64: aGk=",
+            "m:\nt: buffer\nbase64: aGk=",
+            "t: buffer\nbase64: aGk=",
+        ] {
+            assert_eq!(
+                serde_yaml::from_str::<Buffer>(yaml).unwrap(),
+                Buffer::new("hi"),
+                "{yaml}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_prepend() {
+        let mut buffer = Buffer::new("body");
+        buffer.prepend_slice(b"HEAD");
+        assert_eq!(buffer, Buffer::new("HEADbody"));
+
+        buffer.prepend(b'!');
+        buffer.prepend_slice(b"");
+        assert_eq!(buffer, Buffer::new("!HEADbody"));
+
+        let mut buffer = Buffer::default();
+        buffer.prepend_slice(b"HEAD");
+        assert_eq!(buffer, Buffer::new("HEAD"));
+    }
+
+    #[test]
+    fn test_split_into_parts() {
+        let buffer = Buffer::from_fn(10, |i| i as u8);
+
+        let parts = buffer.split_into_parts(3);
+        assert_eq!(
+            parts,
+            [
+                Buffer::new(vec![0, 1, 2, 3]),
+                Buffer::new(vec![4, 5, 6]),
+                Buffer::new(vec![7, 8, 9]),
+            ]
+        );
+
+        assert_eq!(buffer.split_into_parts(1), std::slice::from_ref(&buffer));
+        assert_eq!(buffer.split_into_parts(20).len(), 10);
+        assert!(buffer.split_into_parts(0).is_empty());
+        assert!(Buffer::default().split_into_parts(3).is_empty());
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode() {
+        let config = bincode::config::standard();
+        let buffer = Buffer::new("hello");
+
+        let encoded = bincode::encode_to_vec(&buffer, config).unwrap();
+        assert_eq!(encoded, b"\x05hello");
+
+        let (decoded, len): (Buffer, usize) = bincode::decode_from_slice(&encoded, config).unwrap();
+        assert_eq!(decoded, buffer);
+        assert_eq!(len, 6);
+
+        assert_eq!(
+            bincode::encode_to_vec(buffer.view(1..), config).unwrap(),
+            b"\x04ello"
+        );
+        assert!(bincode::decode_from_slice::<Buffer, _>(b"\x05hi", config).is_err());
+    }
+
+    #[test]
+    fn test_byte_array_rejected() {
+        let error =
+            serde_json::from_str::<Buffer>(r#"{"m":null,"t":"buffer","base64":[104, 105]}"#)
+                .unwrap_err();
+        assert!(
+            error.to_string().contains("expected a base64 string"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_truncate_to_utf8_boundary() {
+        // "é" is 2 bytes, and "😀" is 4.
+        let text = "aé😀";
+
+        for (max_bytes, expected) in [
+            (0, ""),
+            (1, "a"),
+            (2, "a"),
+            (3, "aé"),
+            (4, "aé"),
+            (6, "aé"),
+            (7, "aé😀"),
+            (100, "aé😀"),
+        ] {
+            let mut buffer = Buffer::new(text);
+            buffer.truncate_to_utf8_boundary(max_bytes);
+            assert_eq!(buffer, Buffer::new(expected), "{max_bytes}");
+        }
+
+        // Not UTF-8, so only backs off a few bytes.
+        let mut buffer = Buffer::new(vec![0x80; 10]);
+        buffer.truncate_to_utf8_boundary(8);
+        assert_eq!(buffer.as_ref().len(), 5);
+    }
+
+    #[test]
+    fn test_starts_with() {
+        let buffer = Buffer::new("RBXM\x01\x02");
+
+        assert!(buffer.starts_with(b"RBXM"));
+        assert!(buffer.starts_with(b""));
+        assert!(buffer.view(1..).starts_with(b"BXM"));
+        assert!(!buffer.starts_with(b"RBXL"));
+        assert!(!Buffer::new("RB").starts_with(b"RBXM"));
+    }
+
+    #[test]
+    fn test_as_chunks() {
+        let buffer = Buffer::new((0..10).collect::<Vec<u8>>());
+
+        let (chunks, remainder) = buffer.as_chunks::<4>();
+        assert_eq!(chunks, &[[0, 1, 2, 3], [4, 5, 6, 7]]);
+        assert_eq!(remainder, &[8, 9]);
+
+        let view = buffer.view(..3);
+        let (chunks, remainder) = view.as_chunks::<4>();
+        assert!(chunks.is_empty());
+        assert_eq!(remainder, &[0, 1, 2]);
+    }
+
+    #[test]
+    fn test_to_luau_literal() {
+        assert_eq!(
+            Buffer::new(b"a\x00\"b").to_luau_literal(),
+            r#"buffer.fromstring("a\000\"b")"#
+        );
+        assert_eq!(
+            Buffer::new(b"\\\n1\xff~").to_luau_literal(),
+            r#"buffer.fromstring("\\\0101\255~")"#
+        );
+        assert_eq!(
+            Buffer::default().to_luau_literal(),
+            r#"buffer.fromstring("")"#
+        );
+    }
+
+    #[test]
+    fn test_from_parts() {
+        let data = b"hello world".to_vec();
+
+        let view = BufferSlice::from_parts(&data, 6, 5).unwrap();
+        assert_eq!(view, Buffer::new("world"));
+        assert_eq!(
+            serde_json::to_string(&view).unwrap(),
+            r#"{"m":null,"t":"buffer","base64":"d29ybGQ="}"#
+        );
+        assert_eq!(
+            BufferSlice::from_parts(&data, 11, 0).unwrap(),
+            Buffer::default()
+        );
+
+        assert_eq!(BufferSlice::from_parts(&data, 6, 6), None);
+        assert_eq!(BufferSlice::from_parts(&data, 12, 0), None);
+        assert_eq!(BufferSlice::from_parts(&data, usize::MAX, 2), None);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzbase64_de() {
+        // gzip.compress(b"hello world", mtime=0) in Python.
+        let buffer: Buffer = serde_json::from_str(
+            r#"{"m":null,"t":"buffer","gzbase64":"H4sIAAAAAAACA8tIzcnJVyjPL8pJAQCFEUoNCwAAAA=="}"#,
+        )
+        .unwrap();
+        assert_eq!(buffer, Buffer::new("hello world"));
+
+        assert!(
+            serde_json::from_str::<Buffer>(r#"{"m":null,"t":"buffer","gzbase64":"aGVsbG8="}"#)
+                .is_err()
+        );
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &[0; 100_000]).unwrap();
+        let gzip = encoder.finish().unwrap();
+
+        let decoder = || flate2::read::MultiGzDecoder::new(&gzip[..]);
+        assert_eq!(
+            read_to_end_limited(decoder(), 0, 100_000).unwrap().len(),
+            100_000
+        );
+        let error = read_to_end_limited(decoder(), 0, 99_999).unwrap_err();
+        assert!(matches!(
+            error.get_ref().and_then(|error| error.downcast_ref()),
+            Some(BufferError::LimitExceeded { max: 99_999 })
+        ));
+    }
+
+    #[test]
+    fn test_to_luau_safe_json() {
+        for buffer in [
+            Buffer::default(),
+            Buffer::new("]]--[[\n"),
+            Buffer::new([0xff; 64]),
+            Buffer::new((0..=255).collect::<Vec<u8>>()),
+        ] {
+            let json = buffer.to_luau_safe_json();
+            let source = format!("local json = [[{json}]]");
+
+            assert_eq!(source.find("]]"), Some(source.len() - 2));
+            assert!(!json.contains(['\n', '\r']));
+            assert_eq!(serde_json::from_str::<Buffer>(&json).unwrap(), buffer);
+        }
+    }
+
+    #[test]
+    fn test_resize_with() {
+        let mut buffer = Buffer::new([0xaa, 0xbb]);
+
+        let mut counter = 0;
+        buffer.resize_with(6, || {
+            counter += 1;
+            counter
+        });
+        assert_eq!(buffer, Buffer::new([0xaa, 0xbb, 1, 2, 3, 4]));
+
+        buffer.resize_with(3, || unreachable!());
+        assert_eq!(buffer, Buffer::new([0xaa, 0xbb, 1]));
+    }
+
+    #[test]
+    fn test_chunks_mut() {
+        let mut buffer = Buffer::from_fn(10, |i| i as u8);
+
+        for chunk in buffer.chunks_mut(4) {
+            for (byte, key) in chunk.iter_mut().zip([0xff, 0x0f, 0xf0, 0x00]) {
+                *byte ^= key;
+            }
+        }
+
+        assert_eq!(
+            buffer,
+            Buffer::new([0xff, 0x0e, 0xf2, 3, 0xfb, 0x0a, 0xf6, 7, 0xf7, 0x06])
+        );
+        assert_eq!(buffer.chunks_mut(4).count(), 3);
+        assert_eq!(Buffer::default().chunks_mut(4).count(), 0);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_chunks_mut() {
+        use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+
+        let mut buffer = Buffer::from_fn(1000, |i| i as u8);
+        let mut expected = buffer.clone();
+
+        buffer
+            .par_chunks_mut(4)
+            .enumerate()
+            .for_each(|(index, chunk)| chunk.iter_mut().for_each(|byte| *byte ^= index as u8));
+        for (index, chunk) in expected.chunks_mut(4).enumerate() {
+            chunk.iter_mut().for_each(|byte| *byte ^= index as u8);
+        }
+
+        assert_eq!(buffer, expected);
+        assert_ne!(buffer, Buffer::from_fn(1000, |i| i as u8));
+    }
+
+    #[test]
+    fn test_windows() {
+        let buffer = Buffer::new([1, 2, 3, 4]);
+
+        assert_eq!(
+            buffer.windows(2).collect::<Vec<_>>(),
+            [&[1, 2][..], &[2, 3], &[3, 4]]
+        );
+        assert_eq!(buffer.windows(5).count(), 0);
+    }
+
+    #[test]
+    fn test_flatten() {
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        struct Chunk {
+            data: Buffer,
+            index: u32,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        struct Save {
+            name: String,
+            #[serde(flatten)]
+            chunk: Chunk,
+        }
+
+        let save = Save {
+            name: "save".to_owned(),
+            chunk: Chunk {
+                data: Buffer::new("hello"),
+                index: 3,
+            },
+        };
+
+        let json = serde_json::to_string(&save).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"save","data":{"m":null,"t":"buffer","base64":"aGVsbG8="},"index":3}"#
+        );
+        assert_eq!(serde_json::from_str::<Save>(&json).unwrap(), save);
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Flattened {
+            index: u32,
+            #[serde(flatten)]
+            data: Buffer,
+        }
+
+        let flattened: Flattened = serde_json::from_str(
+            r#"{"index":3,"m":null,"t":"buffer","zbase64":"KLUv/SAFKQAAaGVsbG8="}"#,
+        )
+        .unwrap();
+        assert_eq!(flattened.data, Buffer::new("hello"));
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_json_schema() {
+        let schema = schemars::schema_for!(Buffer);
+        let schema = schema.as_value();
+
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["t"]["const"], "buffer");
+        assert_eq!(schema["required"], serde_json::json!(["t"]));
+        assert_eq!(
+            schema["oneOf"],
+            serde_json::json!([{ "required": ["base64"] }, { "required": ["zbase64"] }])
+        );
+    }
 }
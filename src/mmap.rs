@@ -0,0 +1,46 @@
+use std::{fs::File, io, path::Path};
+
+use memmap2::Mmap;
+
+use crate::{Buffer, DEFAULT_ENCODING};
+
+/// A read-only buffer backed by a memory-mapped file, created with [`Buffer::mmap_file`].
+/// Serializes the same way as [`Buffer`], without copying the file into memory first.
+pub type MmappedBuffer = Buffer<Mmap>;
+
+impl Buffer {
+    /// Memory maps the file at the given path as a read-only buffer.
+    ///
+    /// The file must not be modified or truncated while the map is alive, including by other processes.
+    /// Doing so can change the contents of the buffer from under you, or crash the process when accessed.
+    pub fn mmap_file(path: impl AsRef<Path>) -> io::Result<MmappedBuffer> {
+        let file = File::open(path)?;
+
+        // SAFETY: The requirements are passed on to the caller, as documented above.
+        let map = unsafe { Mmap::map(&file)? };
+
+        Ok(Buffer(map, DEFAULT_ENCODING))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn test_mmap_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"hello world").unwrap();
+        file.flush().unwrap();
+
+        let buffer = Buffer::mmap_file(file.path()).unwrap();
+        assert_eq!(buffer.as_ref(), b"hello world");
+        assert_eq!(buffer.crc32(), Buffer::new("hello world").crc32());
+        assert_eq!(
+            serde_json::to_string(&buffer).unwrap(),
+            serde_json::to_string(&Buffer::new("hello world")).unwrap()
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Byte level deltas between buffers.
+//!
+//! A patch is the length of the target, followed by operations until the end of the patch.
+//! All integers are unsigned LEB128.
+//!
+//! - `0x00 offset len`: Copies `len` bytes starting at `offset` in the source.
+//! - `0x01 len bytes...`: Inserts the next `len` bytes of the patch.
+
+use std::collections::HashMap;
+
+use crate::{Buffer, varint};
+
+const COPY: u8 = 0x00;
+const INSERT: u8 = 0x01;
+
+// Shorter matches than this aren't worth a copy operation.
+const MIN_MATCH: usize = 8;
+
+/// Returned by [`Buffer::apply_patch`] when the patch is invalid for the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchError {
+    /// The patch ended in the middle of an operation.
+    Truncated,
+    /// The patch contained an unknown operation.
+    InvalidOperation(u8),
+    /// A copy operation read past the end of the source.
+    CopyOutOfBounds,
+    /// The patched buffer doesn't have the length the patch said it would.
+    LengthMismatch,
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatchError::Truncated => write!(f, "patch ended in the middle of an operation"),
+            PatchError::InvalidOperation(operation) => {
+                write!(f, "patch contained unknown operation {operation:#04x}")
+            }
+            PatchError::CopyOutOfBounds => write!(f, "patch copied past the end of the source"),
+            PatchError::LengthMismatch => {
+                write!(f, "patched buffer doesn't have the expected length")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl Buffer {
+    /// Creates a patch that turns this buffer into `other` when passed to [`Buffer::apply_patch`].
+    /// See the [`patch`](self) module for the format.
+    pub fn diff(&self, other: &Buffer) -> Vec<u8> {
+        let source = &self.0[..];
+        let target = &other.0[..];
+
+        let mut index = HashMap::new();
+        for (offset, window) in source.windows(MIN_MATCH).enumerate() {
+            index.entry(window).or_insert(offset);
+        }
+
+        let mut patch = Vec::new();
+        varint::write(&mut patch, target.len() as u64);
+
+        let mut literal_start = 0;
+        let mut position = 0;
+
+        while position < target.len() {
+            let source_offset = target
+                .get(position..position + MIN_MATCH)
+                .and_then(|window| index.get(window));
+
+            let Some(&source_offset) = source_offset else {
+                position += 1;
+                continue;
+            };
+
+            let len = source[source_offset..]
+                .iter()
+                .zip(&target[position..])
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            write_insert(&mut patch, &target[literal_start..position]);
+
+            patch.push(COPY);
+            varint::write(&mut patch, source_offset as u64);
+            varint::write(&mut patch, len as u64);
+
+            position += len;
+            literal_start = position;
+        }
+
+        write_insert(&mut patch, &target[literal_start..]);
+
+        patch
+    }
+
+    /// Applies a patch created by [`Buffer::diff`] to this buffer.
+    pub fn apply_patch(&self, patch: &[u8]) -> Result<Buffer, PatchError> {
+        let mut patch = patch;
+
+        let expected_len = read_varint(&mut patch)?;
+        let mut output = Vec::new();
+
+        while let Some((&operation, rest)) = patch.split_first() {
+            patch = rest;
+
+            match operation {
+                COPY => {
+                    let offset = read_varint(&mut patch)?;
+                    let len = read_varint(&mut patch)?;
+                    let end = offset.checked_add(len).ok_or(PatchError::CopyOutOfBounds)?;
+                    let bytes = self.0.get(offset..end).ok_or(PatchError::CopyOutOfBounds)?;
+                    output.extend_from_slice(bytes);
+                }
+
+                INSERT => {
+                    let len = read_varint(&mut patch)?;
+                    if len > patch.len() {
+                        return Err(PatchError::Truncated);
+                    }
+
+                    let (bytes, rest) = patch.split_at(len);
+                    output.extend_from_slice(bytes);
+                    patch = rest;
+                }
+
+                other => return Err(PatchError::InvalidOperation(other)),
+            }
+
+            if output.len() > expected_len {
+                return Err(PatchError::LengthMismatch);
+            }
+        }
+
+        if output.len() != expected_len {
+            return Err(PatchError::LengthMismatch);
+        }
+
+        Ok(Buffer::new(output))
+    }
+}
+
+fn read_varint(patch: &mut &[u8]) -> Result<usize, PatchError> {
+    let (value, len) = varint::read(patch).ok_or(PatchError::Truncated)?;
+    *patch = &patch[len..];
+    usize::try_from(value).map_err(|_| PatchError::Truncated)
+}
+
+fn write_insert(patch: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.is_empty() {
+        return;
+    }
+
+    patch.push(INSERT);
+    varint::write(patch, bytes.len() as u64);
+    patch.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save(coins: u32, name: &str) -> Buffer {
+        let mut buffer = Buffer::new(vec![7; 500]);
+        buffer.append_u32(coins);
+        buffer.append_string(name);
+        buffer.append_string([3; 500]);
+        buffer
+    }
+
+    #[test]
+    fn test_diff_and_apply() {
+        let source = save(100, "Kampfkarren");
+        let target = save(250, "Kampf");
+
+        let patch = source.diff(&target);
+        assert!(patch.len() < 50, "patch is {} bytes", patch.len());
+        assert_eq!(source.apply_patch(&patch), Ok(target));
+    }
+
+    #[test]
+    fn test_diff_unrelated() {
+        for (source, target) in [
+            (Buffer::new("abc"), Buffer::new("hello world")),
+            (Buffer::new("hello world"), Buffer::default()),
+            (Buffer::default(), Buffer::default()),
+            (
+                Buffer::new("aaaaaaaaaaaaaaaa"),
+                Buffer::new("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            ),
+        ] {
+            assert_eq!(source.apply_patch(&source.diff(&target)), Ok(target));
+        }
+    }
+
+    #[test]
+    fn test_apply_invalid() {
+        let source = Buffer::new("hello");
+
+        assert_eq!(source.apply_patch(&[]), Err(PatchError::Truncated));
+        assert_eq!(
+            source.apply_patch(&[5, INSERT, 6, b'a']),
+            Err(PatchError::Truncated)
+        );
+        assert_eq!(
+            source.apply_patch(&[5, 0x02]),
+            Err(PatchError::InvalidOperation(0x02))
+        );
+        assert_eq!(
+            source.apply_patch(&[5, COPY, 1, 5]),
+            Err(PatchError::CopyOutOfBounds)
+        );
+        assert_eq!(
+            source.apply_patch(&[4, COPY, 0, 5]),
+            Err(PatchError::LengthMismatch)
+        );
+        assert_eq!(
+            source.apply_patch(&[6, COPY, 0, 5]),
+            Err(PatchError::LengthMismatch)
+        );
+        assert_eq!(source.apply_patch(&[5, COPY, 0, 5]), Ok(source));
+    }
+}
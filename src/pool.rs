@@ -0,0 +1,192 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Mutex,
+};
+
+use crate::{Base64Error, Buffer};
+
+/// Hands out [`Buffer`]s that keep their allocation when they're dropped, to be reused by the next
+/// [`BufferPool::acquire`].
+#[derive(Debug)]
+pub struct BufferPool {
+    free: Mutex<Vec<Vec<u8>>>,
+    max_idle: usize,
+    max_retained_capacity: usize,
+}
+
+impl BufferPool {
+    /// The number of allocations [`BufferPool::new`] keeps waiting to be reused.
+    pub const DEFAULT_MAX_IDLE: usize = 64;
+
+    /// The capacity [`BufferPool::new`] shrinks allocations to before keeping them, 1 MiB.
+    pub const DEFAULT_MAX_RETAINED_CAPACITY: usize = 1024 * 1024;
+
+    /// Creates an empty pool, with [`BufferPool::DEFAULT_MAX_IDLE`] and
+    /// [`BufferPool::DEFAULT_MAX_RETAINED_CAPACITY`] as its limits.
+    pub fn new() -> Self {
+        Self::with_limits(Self::DEFAULT_MAX_IDLE, Self::DEFAULT_MAX_RETAINED_CAPACITY)
+    }
+
+    /// Creates an empty pool that keeps at most `max_idle` allocations waiting to be reused, freeing any more.
+    /// Allocations with more than `max_retained_capacity` bytes are shrunk to it first, so that one large buffer
+    /// doesn't stay allocated forever.
+    pub fn with_limits(max_idle: usize, max_retained_capacity: usize) -> Self {
+        Self {
+            free: Mutex::default(),
+            max_idle,
+            max_retained_capacity,
+        }
+    }
+
+    /// Returns an empty buffer, reusing the allocation of a dropped one if there is any.
+    pub fn acquire(&self) -> PooledBuffer<'_> {
+        let data = self
+            .free
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .pop()
+            .unwrap_or_default();
+
+        PooledBuffer {
+            buffer: Buffer::new(data),
+            pool: self,
+        }
+    }
+
+    /// Decodes base64, without the Roblox envelope, into a buffer from the pool.
+    pub fn decode_base64(&self, b64: &str) -> Result<PooledBuffer<'_>, Base64Error> {
+        let mut buffer = self.acquire();
+        buffer.append_base64(b64)?;
+        Ok(buffer)
+    }
+
+    /// Returns the number of allocations waiting to be reused.
+    pub fn idle(&self) -> usize {
+        self.free
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`Buffer`] from a [`BufferPool`], which gives its allocation back to the pool when dropped.
+#[derive(Debug)]
+pub struct PooledBuffer<'a> {
+    buffer: Buffer,
+    pool: &'a BufferPool,
+}
+
+impl PooledBuffer<'_> {
+    /// Takes the buffer out of the pool, so its allocation won't be reused.
+    pub fn into_buffer(mut self) -> Buffer {
+        std::mem::take(&mut self.buffer)
+    }
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = Buffer;
+
+    fn deref(&self) -> &Buffer {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Buffer {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        let mut data = std::mem::take(&mut self.buffer).into_vec();
+        data.clear();
+        data.shrink_to(self.pool.max_retained_capacity);
+        if data.capacity() == 0 {
+            return;
+        }
+
+        let mut free = self
+            .pool
+            .free
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if free.len() < self.pool.max_idle {
+            free.push(data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reuse() {
+        let pool = BufferPool::new();
+
+        let mut buffer = pool.acquire();
+        buffer.extend(vec![1u8; 1000]);
+        let capacity = buffer.0.capacity();
+        let pointer = buffer.as_ref().as_ptr();
+        drop(buffer);
+
+        assert_eq!(pool.idle(), 1);
+
+        let buffer = pool.acquire();
+        assert!(buffer.as_ref().is_empty());
+        assert_eq!(buffer.0.capacity(), capacity);
+        assert_eq!(buffer.as_ref().as_ptr(), pointer);
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        let pool = BufferPool::new();
+
+        let buffer = pool.decode_base64("aGVsbG8=").unwrap();
+        assert_eq!(*buffer, Buffer::new("hello"));
+        drop(buffer);
+
+        assert!(pool.decode_base64("aGk!").is_err());
+        assert_eq!(pool.idle(), 1);
+
+        let buffer = pool.decode_base64("aGk=").unwrap();
+        assert_eq!(buffer.into_buffer(), Buffer::new("hi"));
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn test_limits() {
+        let pool = BufferPool::with_limits(2, 1024);
+
+        let mut large = pool.acquire();
+        large.extend(vec![1u8; 100_000]);
+        drop(large);
+
+        assert_eq!(pool.idle(), 1);
+        let buffer = pool.acquire();
+        assert!(buffer.0.capacity() <= 1024);
+        drop(buffer);
+
+        let buffers: Vec<_> = (0..3)
+            .map(|_| {
+                let mut buffer = pool.acquire();
+                buffer.extend([1u8; 10]);
+                buffer
+            })
+            .collect();
+        drop(buffers);
+        assert_eq!(pool.idle(), 2);
+
+        let pool = BufferPool::with_limits(2, 0);
+        pool.decode_base64("aGVsbG8=").unwrap();
+        assert_eq!(pool.idle(), 0);
+    }
+}
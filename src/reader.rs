@@ -0,0 +1,413 @@
+use crate::{Buffer, varint};
+
+/// Returned by [`BufferReader::read_utf16le`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Utf16Error {
+    /// There weren't enough bytes left for the requested code units.
+    UnexpectedEnd,
+    /// The code units contained an unpaired surrogate.
+    UnpairedSurrogate(u16),
+}
+
+impl std::fmt::Display for Utf16Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Utf16Error::UnexpectedEnd => write!(f, "not enough bytes left for the utf-16 string"),
+            Utf16Error::UnpairedSurrogate(unit) => {
+                write!(f, "utf-16 string contained unpaired surrogate {unit:#06x}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Utf16Error {}
+
+/// A position in a [`BufferReader`], created with [`BufferReader::save`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Savepoint {
+    position: usize,
+    bit: u32,
+}
+
+/// Reads sequentially from the contents of a [`Buffer`], created with [`Buffer::reader`].
+#[derive(Debug, Clone)]
+pub struct BufferReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    // Bits already consumed from the byte at `position`, always less than 8.
+    bit: u32,
+}
+
+impl<'a> BufferReader<'a> {
+    /// Creates a reader starting at the beginning of the bytes.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            bit: 0,
+        }
+    }
+
+    /// Returns the bytes being read.
+    pub fn get_ref(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the offset of the byte the reader is currently at.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of whole bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.byte_position()
+    }
+
+    /// Returns whether there are no whole bytes left to read.
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Returns where the reader is, to go back to with [`BufferReader::restore`].
+    pub fn save(&self) -> Savepoint {
+        Savepoint {
+            position: self.position,
+            bit: self.bit,
+        }
+    }
+
+    /// Goes back to where the reader was when the savepoint was made.
+    ///
+    /// # Panics
+    /// Panics if the savepoint is past the end of the bytes, such as one from a reader over other bytes.
+    pub fn restore(&mut self, savepoint: Savepoint) {
+        assert!(
+            savepoint.position + (savepoint.bit != 0) as usize <= self.data.len(),
+            "savepoint is past the end of the reader"
+        );
+
+        self.position = savepoint.position;
+        self.bit = savepoint.bit;
+    }
+
+    /// Runs `parse` on the reader, going back to where it started if it returns an error.
+    pub fn try_parse<T, E>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let savepoint = self.save();
+        let result = parse(self);
+        if result.is_err() {
+            self.restore(savepoint);
+        }
+        result
+    }
+
+    /// Reads the next `len` bytes, or returns `None` without consuming anything if there aren't enough left.
+    ///
+    /// Like all byte reads, this starts at the next whole byte, skipping what's left of a partially read byte.
+    pub fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let start = self.byte_position();
+        let bytes = self.data.get(start..start.checked_add(len)?)?;
+
+        self.position = start + len;
+        self.bit = 0;
+
+        Some(bytes)
+    }
+
+    /// Skips the next `n` bytes, or returns `None` without consuming anything if there aren't enough left.
+    pub fn skip(&mut self, n: usize) -> Option<()> {
+        self.read_bytes(n).map(|_| ())
+    }
+
+    /// Skips to the next offset that is a multiple of `alignment`, such as past padding written by
+    /// [`BufferWriter::pad_to`](crate::BufferWriter::pad_to).
+    /// Returns `None` without consuming anything if that is past the end.
+    ///
+    /// # Panics
+    /// Panics if `alignment` is 0.
+    pub fn align_to(&mut self, alignment: usize) -> Option<()> {
+        assert!(alignment != 0, "alignment must not be 0");
+
+        let start = self.byte_position();
+        self.skip(start.checked_next_multiple_of(alignment)? - start)
+    }
+
+    /// Reads bytes prefixed with their length as unsigned LEB128, such as ones written by
+    /// [`BufferWriter::write_lp_string`](crate::BufferWriter::write_lp_string).
+    /// Returns `None` without consuming anything if the length is invalid or there aren't enough bytes left.
+    pub fn read_lp_string(&mut self) -> Option<&'a [u8]> {
+        let start = self.byte_position();
+        let (len, len_size) = varint::read(self.data.get(start..)?)?;
+        let bytes_start = start + len_size;
+        let bytes = self
+            .data
+            .get(bytes_start..bytes_start.checked_add(usize::try_from(len).ok()?)?)?;
+
+        self.position = bytes_start + bytes.len();
+        self.bit = 0;
+
+        Some(bytes)
+    }
+
+    /// Reads a little-endian unsigned integer of `byte_count` bytes, such as a 3 byte integer.
+    /// Returns `None` without consuming anything if `byte_count` isn't between 1 and 8 or there aren't enough bytes left.
+    pub fn read_uint(&mut self, byte_count: usize) -> Option<u64> {
+        if !(1..=8).contains(&byte_count) {
+            return None;
+        }
+
+        let mut bytes = [0; 8];
+        bytes[..byte_count].copy_from_slice(self.read_bytes(byte_count)?);
+        Some(u64::from_le_bytes(bytes))
+    }
+
+    /// Reads `code_units` little-endian UTF-16 code units as a string, such as one written by
+    /// [`BufferWriter::write_utf16le`](crate::BufferWriter::write_utf16le).
+    /// Nothing is consumed if there aren't enough bytes left or the string is invalid.
+    pub fn read_utf16le(&mut self, code_units: usize) -> Result<String, Utf16Error> {
+        let len = code_units.checked_mul(2).ok_or(Utf16Error::UnexpectedEnd)?;
+        let start = self.byte_position();
+        let bytes = self
+            .data
+            .get(start..start.checked_add(len).ok_or(Utf16Error::UnexpectedEnd)?)
+            .ok_or(Utf16Error::UnexpectedEnd)?;
+
+        let string = char::decode_utf16(
+            bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]])),
+        )
+        .collect::<Result<String, _>>()
+        .map_err(|error| Utf16Error::UnpairedSurrogate(error.unpaired_surrogate()))?;
+
+        self.position = start + len;
+        self.bit = 0;
+
+        Ok(string)
+    }
+
+    /// Reads bytes up to the next null byte, consuming the null but not returning it.
+    /// Returns `None` without consuming anything if there is no null byte left.
+    pub fn read_cstr(&mut self) -> Option<&'a [u8]> {
+        let start = self.byte_position();
+        let len = self.data.get(start..)?.iter().position(|&byte| byte == 0)?;
+
+        self.position = start + len + 1;
+        self.bit = 0;
+
+        Some(&self.data[start..start + len])
+    }
+
+    // Where byte reads start from, skipping the rest of a partially read byte.
+    fn byte_position(&self) -> usize {
+        self.position + (self.bit != 0) as usize
+    }
+
+    /// Reads `n` bits, least significant bit first, into the low bits of the result.
+    /// Returns `None` without consuming anything if `n` is over 64 or there aren't enough bits left.
+    pub fn read_bits(&mut self, n: u32) -> Option<u64> {
+        if n > 64 || n as usize > (self.data.len() - self.position) * 8 - self.bit as usize {
+            return None;
+        }
+
+        let mut value = 0;
+        let mut read = 0;
+
+        while read < n {
+            let take = (8 - self.bit).min(n - read);
+            let bits = (self.data[self.position] >> self.bit) as u64 & ((1 << take) - 1);
+
+            value |= bits << read;
+            read += take;
+            self.bit += take;
+
+            if self.bit == 8 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+
+        Some(value)
+    }
+}
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Creates a [`BufferReader`] over the contents of the buffer.
+    pub fn reader(&self) -> BufferReader<'_> {
+        BufferReader::new(self.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_bits() {
+        let buffer = Buffer::new(vec![0b1010_1101, 0b0000_0110]);
+        let mut reader = buffer.reader();
+
+        assert_eq!(reader.read_bits(3), Some(0b101));
+        assert_eq!(reader.read_bits(5), Some(0b10101));
+        assert_eq!(reader.position(), 1);
+
+        assert_eq!(reader.read_bits(9), None);
+        assert_eq!(reader.read_bits(8), Some(0b0000_0110));
+        assert_eq!(reader.read_bits(1), None);
+    }
+
+    #[test]
+    fn test_read_bits_across_bytes() {
+        let buffer = Buffer::new(vec![0b1010_1101, 0b0000_0110]);
+        let mut reader = buffer.reader();
+
+        assert_eq!(reader.read_bits(3), Some(0b101));
+        assert_eq!(reader.read_bits(7), Some(0b1010101));
+        assert_eq!(reader.read_bits(6), Some(0b000001));
+        assert_eq!(reader.read_bits(65), None);
+    }
+
+    #[test]
+    fn test_read_bytes() {
+        let buffer = Buffer::new(vec![1, 2, 3, 4, 5]);
+        let mut reader = buffer.reader();
+
+        assert_eq!(reader.read_bytes(2), Some(&[1, 2][..]));
+        assert_eq!(reader.remaining(), 3);
+        assert_eq!(reader.read_bytes(4), None);
+        assert_eq!(reader.read_bytes(usize::MAX), None);
+
+        assert_eq!(reader.read_bits(4), Some(3));
+        assert_eq!(reader.remaining(), 2);
+        assert_eq!(reader.read_bytes(2), Some(&[4, 5][..]));
+        assert!(reader.is_empty());
+        assert_eq!(reader.read_bytes(0), Some(&[][..]));
+    }
+
+    #[test]
+    fn test_read_utf16le() {
+        let buffer = Buffer::new(vec![
+            b'h', 0, b'i', 0, 0x3d, 0xd8, 0x00, 0xde, 0x00, 0xd8, b'!', 0,
+        ]);
+        let mut reader = buffer.reader();
+
+        assert_eq!(reader.read_utf16le(7), Err(Utf16Error::UnexpectedEnd));
+        assert_eq!(
+            reader.read_utf16le(usize::MAX),
+            Err(Utf16Error::UnexpectedEnd)
+        );
+        assert_eq!(reader.read_utf16le(4).as_deref(), Ok("hi\u{1f600}"));
+        assert_eq!(
+            reader.read_utf16le(2),
+            Err(Utf16Error::UnpairedSurrogate(0xd800))
+        );
+        assert_eq!(reader.remaining(), 4);
+    }
+
+    #[test]
+    fn test_read_cstr_unterminated() {
+        let buffer = Buffer::new("abc");
+        let mut reader = buffer.reader();
+
+        assert_eq!(reader.read_cstr(), None);
+        assert_eq!(reader.remaining(), 3);
+    }
+
+    #[test]
+    fn test_read_uint() {
+        let buffer = Buffer::new(vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09]);
+        let mut reader = buffer.reader();
+
+        assert_eq!(reader.read_uint(0), None);
+        assert_eq!(reader.read_uint(9), None);
+        assert_eq!(reader.read_uint(3), Some(0x03_0201));
+        assert_eq!(reader.read_uint(5), Some(0x08_0706_0504));
+        assert_eq!(reader.read_uint(2), None);
+        assert_eq!(reader.read_uint(1), Some(0x09));
+    }
+
+    #[test]
+    fn test_read_lp_string_invalid() {
+        // The length says 5 bytes, but only 3 follow.
+        let buffer = Buffer::new(b"\x05abc".to_vec());
+        let mut reader = buffer.reader();
+        assert_eq!(reader.read_lp_string(), None);
+        assert_eq!(reader.remaining(), 4);
+
+        // The length never ends.
+        let buffer = Buffer::new(vec![0x80; 3]);
+        assert_eq!(buffer.reader().read_lp_string(), None);
+    }
+
+    #[test]
+    fn test_savepoint() {
+        let buffer = Buffer::new([1, 2, 3, 4, 5]);
+        let mut reader = buffer.reader();
+        reader.read_bytes(1).unwrap();
+
+        let savepoint = reader.save();
+        reader.read_bits(3).unwrap();
+        reader.read_bytes(2).unwrap();
+        reader.restore(savepoint);
+        assert_eq!(reader.position(), 1);
+        assert_eq!(reader.read_bytes(1), Some(&[2][..]));
+
+        let result = reader.try_parse(|reader| {
+            let tag = reader.read_bytes(1).ok_or("tag")?;
+            let value = reader.read_uint(4).ok_or("value")?;
+            Ok((tag, value))
+        });
+        assert_eq!(result, Err("value"));
+        assert_eq!(reader.position(), 2);
+
+        let result = reader.try_parse(|reader| reader.read_bytes(2).ok_or(()));
+        assert_eq!(result, Ok(&[3, 4][..]));
+        assert_eq!(reader.position(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "savepoint is past the end of the reader")]
+    fn test_restore_partial_byte_past_end() {
+        let long = Buffer::new([1, 2]);
+        let mut long_reader = long.reader();
+        long_reader.read_bytes(1).unwrap();
+        long_reader.read_bits(3).unwrap();
+
+        let short = Buffer::new([1]);
+        short.reader().restore(long_reader.save());
+    }
+
+    #[test]
+    fn test_align_to() {
+        let mut writer = crate::BufferWriter::new();
+        writer.write_uint(7, 1);
+        writer.pad_to(4);
+        writer.write_uint(0xdeadbeef, 4);
+        writer.write_bits(1, 1);
+        writer.pad_to(4);
+        let buffer = writer.into_buffer();
+        assert_eq!(buffer.as_ref().len(), 12);
+
+        let mut reader = buffer.reader();
+        assert_eq!(reader.read_uint(1), Some(7));
+        reader.align_to(4).unwrap();
+        assert_eq!(reader.position(), 4);
+        assert_eq!(reader.read_uint(4), Some(0xdeadbeef));
+
+        reader.align_to(4).unwrap();
+        assert_eq!(reader.position(), 8);
+
+        reader.read_bits(1).unwrap();
+        assert_eq!(reader.align_to(16), None);
+        reader.align_to(4).unwrap();
+        assert_eq!(reader.position(), 12);
+
+        assert_eq!(Buffer::new([1, 2, 3]).reader().skip(4), None);
+        let buffer = Buffer::new([1, 2, 3]);
+        let mut reader = buffer.reader();
+        reader.skip(2).unwrap();
+        assert_eq!(reader.read_uint(1), Some(3));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::Buffer;
+
+/// Returned by [`Buffer::parse_records`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError<E> {
+    /// Parsing a record failed.
+    Record {
+        /// The index of the record that failed to parse.
+        index: usize,
+        /// The error returned while parsing it.
+        error: E,
+    },
+    /// The buffer ended with a partial record.
+    TrailingBytes {
+        /// The number of bytes left over after the last whole record.
+        len: usize,
+    },
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for ParseError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Record { index, error } => {
+                write!(f, "failed to parse record {index}: {error}")
+            }
+            ParseError::TrailingBytes { len } => {
+                write!(f, "buffer ended with a partial record of {len} bytes")
+            }
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for ParseError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Record { error, .. } => Some(error),
+            ParseError::TrailingBytes { .. } => None,
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Splits the buffer into records of `record_size` bytes and parses each one with `f`.
+    /// Stops at the first record that fails to parse, and errors if the last record is cut off.
+    ///
+    /// # Panics
+    /// Panics if `record_size` is 0.
+    pub fn parse_records<R, E, F>(&self, record_size: usize, f: F) -> Result<Vec<R>, ParseError<E>>
+    where
+        F: Fn(&[u8]) -> Result<R, E>,
+    {
+        assert!(record_size != 0, "record size must not be 0");
+
+        let records = self.as_ref().chunks_exact(record_size);
+
+        if !records.remainder().is_empty() {
+            return Err(ParseError::TrailingBytes {
+                len: records.remainder().len(),
+            });
+        }
+
+        records
+            .enumerate()
+            .map(|(index, record)| f(record).map_err(|error| ParseError::Record { index, error }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_u32(record: &[u8]) -> Result<u32, std::array::TryFromSliceError> {
+        record.try_into().map(u32::from_le_bytes)
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let buffer = Buffer::new(vec![1, 0, 0, 0, 2, 0, 0, 0, 0, 1, 0, 0]);
+        assert_eq!(buffer.parse_records(4, parse_u32).unwrap(), [1, 2, 256]);
+
+        let buffer = Buffer::new(vec![1, 0, 0, 0, 2, 0]);
+        assert!(matches!(
+            buffer.parse_records(4, parse_u32),
+            Err(ParseError::TrailingBytes { len: 2 })
+        ));
+
+        let buffer = Buffer::new(vec![1, 2, 3, 4]);
+        assert_eq!(
+            buffer.parse_records(2, |record| match record[0] {
+                3 => Err("three"),
+                byte => Ok(byte),
+            }),
+            Err(ParseError::Record {
+                index: 1,
+                error: "three"
+            })
+        );
+
+        assert!(
+            Buffer::default()
+                .parse_records(4, parse_u32)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
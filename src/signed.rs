@@ -0,0 +1,123 @@
+//! Buffers signed with an HMAC, enabled with the `hmac` feature.
+
+use data_encoding::BASE64;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{Buffer, BufferError};
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn hmac(key: &[u8], data: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac
+}
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Returns the buffer as [`Buffer::to_canonical_json`], with an `hmac` key holding the base64 of an HMAC-SHA256
+    /// of the contents, to be checked by [`Buffer::from_signed_json`].
+    pub fn to_signed_json(&self, key: &[u8]) -> String {
+        let tag = hmac(key, self.0.as_ref()).finalize().into_bytes();
+
+        let mut json = self.to_canonical_json();
+        json.pop(); // }
+        json.push_str(r#","hmac":""#);
+        BASE64.encode_append(&tag, &mut json);
+        json.push_str(r#""}"#);
+        json
+    }
+}
+
+impl Buffer {
+    /// Reads a buffer written by [`Buffer::to_signed_json`], checking its HMAC in constant time.
+    /// Fails with [`BufferError::Json`] if it isn't a buffer, and [`BufferError::SignatureMismatch`] if the HMAC is
+    /// missing or doesn't match.
+    pub fn from_signed_json(s: &str, key: &[u8]) -> Result<Self, BufferError> {
+        let mut value: serde_json::Value = serde_json::from_str(s)?;
+
+        let tag = value
+            .as_object_mut()
+            .and_then(|object| object.remove("hmac"));
+
+        // Decoded first, so that anything that isn't a buffer fails to decode rather than to verify.
+        let buffer: Buffer = serde_json::from_value(value)?;
+
+        let tag = match tag {
+            Some(serde_json::Value::String(tag)) => BASE64
+                .decode(tag.as_bytes())
+                .map_err(|_| BufferError::SignatureMismatch)?,
+            _ => return Err(BufferError::SignatureMismatch),
+        };
+
+        hmac(key, &buffer.0)
+            .verify_slice(&tag)
+            .map_err(|_| BufferError::SignatureMismatch)?;
+
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_signature() {
+        let buffer = Buffer::new("hello");
+        let json = buffer.to_signed_json(b"secret");
+
+        assert!(json.starts_with(r#"{"m":null,"t":"buffer","base64":"aGVsbG8=","hmac":""#));
+        assert_eq!(Buffer::from_signed_json(&json, b"secret").unwrap(), buffer);
+
+        let empty = Buffer::default().to_signed_json(b"");
+        assert_eq!(
+            Buffer::from_signed_json(&empty, b"").unwrap(),
+            Buffer::default()
+        );
+    }
+
+    #[test]
+    fn test_wrong_key() {
+        let json = Buffer::new("hello").to_signed_json(b"secret");
+
+        assert!(matches!(
+            Buffer::from_signed_json(&json, b"guess"),
+            Err(BufferError::SignatureMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_tampered() {
+        let json = Buffer::new("hello").to_signed_json(b"secret");
+
+        let tampered = json.replace("aGVsbG8=", "aGVsbA==");
+        assert_ne!(tampered, json);
+        assert!(matches!(
+            Buffer::from_signed_json(&tampered, b"secret"),
+            Err(BufferError::SignatureMismatch)
+        ));
+
+        let unsigned = Buffer::new("hello").to_canonical_json();
+        assert!(matches!(
+            Buffer::from_signed_json(&unsigned, b"secret"),
+            Err(BufferError::SignatureMismatch)
+        ));
+
+        let invalid = json.replace("aGVsbG8=", "aGVsbG8");
+        assert!(matches!(
+            Buffer::from_signed_json(&invalid, b"secret"),
+            Err(BufferError::Json(_))
+        ));
+
+        for not_an_object in ["[]", "5", r#""aGVsbG8=""#] {
+            assert!(
+                matches!(
+                    Buffer::from_signed_json(not_an_object, b"secret"),
+                    Err(BufferError::Json(_))
+                ),
+                "{not_an_object}"
+            );
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Set operations on buffers of sorted bytes, enabled with the `sorted-set` feature.
+//!
+//! Each of these assumes both buffers are sorted and have no duplicates. If either doesn't, the result isn't
+//! meaningful, but is still made up of bytes from the buffers and doesn't panic.
+
+use std::cmp::Ordering;
+
+use crate::Buffer;
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Returns the bytes in either buffer, sorted.
+    pub fn union_sorted<U: AsRef<[u8]>>(&self, other: &Buffer<U>) -> Buffer {
+        merge(self.as_ref(), other.as_ref(), true, true, true)
+    }
+
+    /// Returns the bytes in both buffers, sorted.
+    pub fn intersection_sorted<U: AsRef<[u8]>>(&self, other: &Buffer<U>) -> Buffer {
+        merge(self.as_ref(), other.as_ref(), false, true, false)
+    }
+
+    /// Returns the bytes in this buffer that aren't in `other`, sorted.
+    pub fn difference_sorted<U: AsRef<[u8]>>(&self, other: &Buffer<U>) -> Buffer {
+        merge(self.as_ref(), other.as_ref(), true, false, false)
+    }
+}
+
+// Walks both sorted inputs at once, keeping bytes only in `a`, in both, or only in `b` as asked.
+fn merge(a: &[u8], b: &[u8], only_a: bool, both: bool, only_b: bool) -> Buffer {
+    let mut data = Vec::new();
+    let (mut a, mut b) = (a.iter().peekable(), b.iter().peekable());
+
+    loop {
+        match (a.peek(), b.peek()) {
+            (Some(&&x), Some(&&y)) => match x.cmp(&y) {
+                Ordering::Less => {
+                    if only_a {
+                        data.push(x);
+                    }
+                    a.next();
+                }
+                Ordering::Greater => {
+                    if only_b {
+                        data.push(y);
+                    }
+                    b.next();
+                }
+                Ordering::Equal => {
+                    if both {
+                        data.push(x);
+                    }
+                    a.next();
+                    b.next();
+                }
+            },
+            (Some(_), None) => {
+                if only_a {
+                    data.extend(a);
+                }
+                break;
+            }
+            (None, Some(_)) => {
+                if only_b {
+                    data.extend(b);
+                }
+                break;
+            }
+            (None, None) => break,
+        }
+    }
+
+    Buffer::new(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_sorted() {
+        let a = Buffer::new([1, 3, 5, 7]);
+        let b = Buffer::new([2, 3, 4, 9, 10]);
+
+        assert_eq!(a.union_sorted(&b), Buffer::new([1, 2, 3, 4, 5, 7, 9, 10]));
+        assert_eq!(a.union_sorted(&Buffer::default()), a);
+        assert_eq!(Buffer::default().union_sorted(&b), b);
+    }
+
+    #[test]
+    fn test_intersection_sorted() {
+        let a = Buffer::new([1, 3, 5, 7, 9]);
+        let b = Buffer::new([2, 3, 4, 9, 10]);
+
+        assert_eq!(a.intersection_sorted(&b), Buffer::new([3, 9]));
+        assert_eq!(a.intersection_sorted(&Buffer::default()), Buffer::default());
+    }
+
+    #[test]
+    fn test_difference_sorted() {
+        let a = Buffer::new([1, 3, 5, 7, 9]);
+        let b = Buffer::new([2, 3, 4, 9, 10]);
+
+        assert_eq!(a.difference_sorted(&b), Buffer::new([1, 5, 7]));
+        assert_eq!(b.difference_sorted(&a), Buffer::new([2, 4, 10]));
+    }
+}
@@ -0,0 +1,79 @@
+//! Deserializes a Roblox buffer that may have been stringified, for use with
+//! `#[serde(deserialize_with = "roblox_buffer::stringified::deserialize")]`. Enabled with the `json` feature.
+//!
+//! Some exporters encode buffers twice, storing the buffer JSON as a string instead of an object.
+//! This accepts either, unwrapping at most one level of stringification.
+//!
+//! ```
+//! #[derive(serde::Deserialize)]
+//! struct Save {
+//!     #[serde(deserialize_with = "roblox_buffer::stringified::deserialize")]
+//!     data: roblox_buffer::Buffer,
+//! }
+//! ```
+
+use serde::{Deserialize, Deserializer, de};
+
+use crate::Buffer;
+
+/// Deserializes a Roblox buffer, or a string containing the JSON of one.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Buffer, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct StringifiedVisitor;
+
+    impl<'de> de::Visitor<'de> for StringifiedVisitor {
+        type Value = Buffer;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a buffer, or a string containing buffer json")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            serde_json::from_str(v).map_err(E::custom)
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            Buffer::deserialize(de::value::MapAccessDeserializer::new(map))
+        }
+    }
+
+    deserializer.deserialize_any(StringifiedVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::Buffer;
+
+    #[derive(Debug, Deserialize)]
+    struct Save {
+        #[serde(deserialize_with = "crate::stringified::deserialize")]
+        data: Buffer,
+    }
+
+    #[test]
+    fn test_stringified() {
+        let save: Save = serde_json::from_str(
+            r#"{"data":"{\"m\":null,\"t\":\"buffer\",\"base64\":\"aGVsbG8gd29ybGQ=\"}"}"#,
+        )
+        .unwrap();
+        assert_eq!(save.data, Buffer::new("hello world"));
+
+        let save: Save =
+            serde_json::from_str(r#"{"data":{"m":null,"t":"buffer","base64":"aGVsbG8gd29ybGQ="}}"#)
+                .unwrap();
+        assert_eq!(save.data, Buffer::new("hello world"));
+
+        // Only one level is unwrapped.
+        let twice = serde_json::to_string(
+            &serde_json::to_string(r#"{"m":null,"t":"buffer","base64":"aGk="}"#).unwrap(),
+        )
+        .unwrap();
+        assert!(serde_json::from_str::<Save>(&format!(r#"{{"data":{twice}}}"#)).is_err());
+
+        assert!(serde_json::from_str::<Save>(r#"{"data":"aGk="}"#).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{BufferReader, OutOfBounds};
+
+/// Iterates over tag-length-value records, where each record is a little endian tag, a little endian length,
+/// and then that many bytes of value.
+///
+/// Yields an [`OutOfBounds`] error and then stops if the last record is cut off.
+#[derive(Debug, Clone)]
+pub struct TlvReader<'a> {
+    reader: BufferReader<'a>,
+    tag_size: usize,
+    len_size: usize,
+    done: bool,
+}
+
+impl<'a> TlvReader<'a> {
+    /// Creates a reader of records with tags of `tag_size` bytes and lengths of `len_size` bytes.
+    ///
+    /// # Panics
+    /// Panics if either size isn't between 1 and 8.
+    pub fn new(reader: BufferReader<'a>, tag_size: usize, len_size: usize) -> Self {
+        assert!((1..=8).contains(&tag_size), "tag size must be 1 to 8 bytes");
+        assert!(
+            (1..=8).contains(&len_size),
+            "length size must be 1 to 8 bytes"
+        );
+
+        Self {
+            reader,
+            tag_size,
+            len_size,
+            done: false,
+        }
+    }
+
+    fn read(&mut self, len: usize) -> Result<&'a [u8], OutOfBounds> {
+        let offset = self.reader.get_ref().len() - self.reader.remaining();
+
+        self.reader.read_bytes(len).ok_or(OutOfBounds {
+            offset,
+            len,
+            buffer_len: self.reader.get_ref().len(),
+        })
+    }
+
+    fn read_le(&mut self, size: usize) -> Result<u64, OutOfBounds> {
+        let mut bytes = [0; 8];
+        bytes[..size].copy_from_slice(self.read(size)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn read_record(&mut self) -> Result<(u64, &'a [u8]), OutOfBounds> {
+        let tag = self.read_le(self.tag_size)?;
+        let len = self.read_le(self.len_size)?;
+        let value = self.read(usize::try_from(len).unwrap_or(usize::MAX))?;
+        Ok((tag, value))
+    }
+}
+
+impl<'a> Iterator for TlvReader<'a> {
+    type Item = Result<(u64, &'a [u8]), OutOfBounds>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.reader.is_empty() {
+            return None;
+        }
+
+        let record = self.read_record();
+        self.done = record.is_err();
+        Some(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Buffer;
+
+    #[test]
+    fn test_tlv_reader() {
+        let buffer = Buffer::new(vec![
+            1, 3, 0, b'a', b'b', b'c', //
+            2, 0, 0, //
+            7, 1, 0, 0xff,
+        ]);
+
+        let records: Vec<_> = TlvReader::new(buffer.reader(), 1, 2).collect();
+        assert_eq!(
+            records,
+            vec![
+                Ok((1, &b"abc"[..])),
+                Ok((2, &b""[..])),
+                Ok((7, &[0xff][..])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tlv_reader_truncated() {
+        let buffer = Buffer::new(vec![1, 1, b'a', 2, 5, b'b', b'c']);
+
+        let mut reader = TlvReader::new(buffer.reader(), 1, 1);
+        assert_eq!(reader.next(), Some(Ok((1, &b"a"[..]))));
+        assert_eq!(
+            reader.next(),
+            Some(Err(OutOfBounds {
+                offset: 5,
+                len: 5,
+                buffer_len: 7,
+            }))
+        );
+        assert_eq!(reader.next(), None);
+    }
+}
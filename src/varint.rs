@@ -0,0 +1,71 @@
+//! Unsigned LEB128, 7 bits per byte with the high bit set on every byte but the last.
+
+/// Appends `value` as LEB128.
+pub(crate) fn write(output: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            output.push(byte);
+            return;
+        }
+
+        output.push(byte | 0x80);
+    }
+}
+
+/// Reads LEB128 from the start of `input`, returning the value and how many bytes it took.
+/// Returns `None` if `input` ends first or the value doesn't fit in a `u64`.
+pub(crate) fn read(input: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+
+    for (index, &byte) in input.iter().enumerate().take(10) {
+        let bits = (byte & 0x7f) as u64;
+        let shift = index as u32 * 7;
+
+        if shift == 63 && bits > 1 {
+            return None;
+        }
+
+        value |= bits << shift;
+
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint_round_trip() {
+        for value in [0, 1, 127, 128, 300, 16383, 16384, u32::MAX as u64, u64::MAX] {
+            let mut output = Vec::new();
+            write(&mut output, value);
+            assert_eq!(read(&output), Some((value, output.len())), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_varint_known() {
+        let mut output = Vec::new();
+        write(&mut output, 300);
+        assert_eq!(output, [0xac, 0x02]);
+    }
+
+    #[test]
+    fn test_varint_invalid() {
+        assert_eq!(read(&[]), None);
+        assert_eq!(read(&[0x80]), None);
+        assert_eq!(read(&[0xff; 10]), None);
+        assert_eq!(
+            read(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02]),
+            None
+        );
+    }
+}
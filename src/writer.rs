@@ -0,0 +1,229 @@
+use crate::{Buffer, varint};
+
+/// Returned by [`BufferWriter::write_cstr`] when the bytes contain a null byte, at the given position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InteriorNulError(pub usize);
+
+impl std::fmt::Display for InteriorNulError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "c string contains a null byte at position {}", self.0)
+    }
+}
+
+impl std::error::Error for InteriorNulError {}
+
+/// Builds a [`Buffer`] by writing to the end of it.
+#[derive(Debug, Clone, Default)]
+pub struct BufferWriter {
+    data: Vec<u8>,
+    // Bits already used in the last byte, or 0 if it is full.
+    bit: u32,
+}
+
+impl BufferWriter {
+    /// Creates an empty writer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the low `n` bits of `value`, least significant bit first.
+    /// Bits are packed into the last byte until it is full, the same way [`BufferReader::read_bits`](crate::BufferReader::read_bits) reads them.
+    ///
+    /// # Panics
+    /// Panics if `n` is over 64.
+    pub fn write_bits(&mut self, value: u64, n: u32) {
+        assert!(n <= 64, "can't write more than 64 bits at once");
+
+        let mut written = 0;
+
+        while written < n {
+            if self.bit == 0 {
+                self.data.push(0);
+            }
+
+            let take = (8 - self.bit).min(n - written);
+            let bits = (value >> written) as u8 & ((1u16 << take) - 1) as u8;
+
+            *self.data.last_mut().unwrap() |= bits << self.bit;
+            written += take;
+            self.bit = (self.bit + take) % 8;
+        }
+    }
+
+    /// Writes zeros until the length is a multiple of `alignment`, to be skipped with
+    /// [`BufferReader::align_to`](crate::BufferReader::align_to).
+    ///
+    /// # Panics
+    /// Panics if `alignment` is 0.
+    pub fn pad_to(&mut self, alignment: usize) {
+        assert!(alignment != 0, "alignment must not be 0");
+
+        self.bit = 0;
+        self.data
+            .resize(self.data.len().next_multiple_of(alignment), 0);
+    }
+
+    /// Writes bytes prefixed with their length as unsigned LEB128, to be read back with
+    /// [`BufferReader::read_lp_string`](crate::BufferReader::read_lp_string).
+    pub fn write_lp_string(&mut self, bytes: &[u8]) {
+        self.bit = 0;
+        varint::write(&mut self.data, bytes.len() as u64);
+        self.data.extend_from_slice(bytes);
+    }
+
+    /// Writes the low `byte_count` bytes of `value` as a little-endian unsigned integer, such as a 3 byte integer.
+    ///
+    /// # Panics
+    /// Panics if `byte_count` isn't between 1 and 8.
+    pub fn write_uint(&mut self, value: u64, byte_count: usize) {
+        assert!(
+            (1..=8).contains(&byte_count),
+            "byte count must be 1 to 8 bytes"
+        );
+
+        self.bit = 0;
+        self.data
+            .extend_from_slice(&value.to_le_bytes()[..byte_count]);
+    }
+
+    /// Writes a string as little-endian UTF-16 code units, without a length or terminator.
+    /// Like all byte writes, this starts at the next whole byte.
+    pub fn write_utf16le(&mut self, s: &str) {
+        self.bit = 0;
+        self.data
+            .extend(s.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+    }
+
+    /// Writes bytes followed by a null terminator, to be read back with [`BufferReader::read_cstr`](crate::BufferReader::read_cstr).
+    /// Nothing is written if the bytes contain a null byte.
+    pub fn write_cstr(&mut self, bytes: &[u8]) -> Result<(), InteriorNulError> {
+        if let Some(position) = bytes.iter().position(|&byte| byte == 0) {
+            return Err(InteriorNulError(position));
+        }
+
+        self.bit = 0;
+        self.data.extend_from_slice(bytes);
+        self.data.push(0);
+
+        Ok(())
+    }
+
+    /// Returns the written buffer.
+    pub fn into_buffer(self) -> Buffer {
+        Buffer::new(self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_bits() {
+        let mut writer = BufferWriter::new();
+        writer.write_bits(0b101, 3);
+        writer.write_bits(0b10101, 5);
+        writer.write_bits(0b110, 8);
+        assert_eq!(
+            writer.into_buffer(),
+            Buffer::new(vec![0b1010_1101, 0b0000_0110])
+        );
+    }
+
+    #[test]
+    fn test_write_bits_round_trip() {
+        let mut writer = BufferWriter::new();
+        writer.write_bits(0b101, 3);
+        writer.write_bits(0b1_0110_1001, 9);
+        writer.write_bits(u64::MAX, 64);
+        writer.write_bits(0, 0);
+        writer.write_bits(1, 1);
+
+        let buffer = writer.into_buffer();
+        assert_eq!(buffer.as_ref().len(), 10);
+
+        let mut reader = buffer.reader();
+        assert_eq!(reader.read_bits(3), Some(0b101));
+        assert_eq!(reader.read_bits(9), Some(0b1_0110_1001));
+        assert_eq!(reader.read_bits(64), Some(u64::MAX));
+        assert_eq!(reader.read_bits(1), Some(1));
+    }
+
+    #[test]
+    fn test_utf16le_round_trip() {
+        let text = "hi \u{1f600} \u{e9}";
+
+        let mut writer = BufferWriter::new();
+        writer.write_bits(1, 1);
+        writer.write_utf16le(text);
+        writer.write_utf16le("");
+
+        let buffer = writer.into_buffer();
+        assert_eq!(buffer.as_ref().len(), 1 + text.encode_utf16().count() * 2);
+        assert_eq!(buffer.as_ref()[7..11], [0x3d, 0xd8, 0x00, 0xde]);
+
+        let mut reader = buffer.reader();
+        assert_eq!(reader.read_bits(1), Some(1));
+        assert_eq!(
+            reader.read_utf16le(text.encode_utf16().count()).as_deref(),
+            Ok(text)
+        );
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_cstr_round_trip() {
+        let mut writer = BufferWriter::new();
+        writer.write_cstr(b"hello").unwrap();
+        writer.write_cstr(b"").unwrap();
+        writer.write_cstr(b"world").unwrap();
+        assert_eq!(writer.write_cstr(b"a\0b"), Err(InteriorNulError(1)));
+
+        let buffer = writer.into_buffer();
+        assert_eq!(buffer.as_ref(), b"hello\0\0world\0");
+
+        let mut reader = buffer.reader();
+        assert_eq!(reader.read_cstr(), Some(&b"hello"[..]));
+        assert_eq!(reader.read_cstr(), Some(&b""[..]));
+        assert_eq!(reader.read_cstr(), Some(&b"world"[..]));
+        assert_eq!(reader.read_cstr(), None);
+    }
+
+    #[test]
+    fn test_write_uint() {
+        let mut writer = BufferWriter::new();
+        writer.write_uint(0x03_0201, 3);
+        writer.write_uint(0x08_0706_0504, 5);
+        writer.write_uint(u64::MAX, 8);
+        writer.write_uint(0x1234, 1);
+
+        let buffer = writer.into_buffer();
+        assert_eq!(buffer.as_ref()[..8], [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let mut reader = buffer.reader();
+        assert_eq!(reader.read_uint(3), Some(0x03_0201));
+        assert_eq!(reader.read_uint(5), Some(0x08_0706_0504));
+        assert_eq!(reader.read_uint(8), Some(u64::MAX));
+        assert_eq!(reader.read_uint(1), Some(0x34));
+    }
+
+    #[test]
+    fn test_lp_string_round_trip() {
+        let long = vec![b'a'; 300];
+
+        let mut writer = BufferWriter::new();
+        writer.write_lp_string(b"hello");
+        writer.write_lp_string(&long);
+        writer.write_lp_string(b"");
+
+        let buffer = writer.into_buffer();
+        assert_eq!(buffer.as_ref()[..6], *b"\x05hello");
+        assert_eq!(buffer.as_ref()[6..8], [0xac, 0x02]);
+
+        let mut reader = buffer.reader();
+        assert_eq!(reader.read_lp_string(), Some(&b"hello"[..]));
+        assert_eq!(reader.read_lp_string(), Some(&long[..]));
+        assert_eq!(reader.read_lp_string(), Some(&b""[..]));
+        assert_eq!(reader.read_lp_string(), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Z85 text encoding, enabled with the `z85` feature.
+
+use crate::Buffer;
+
+const ALPHABET: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+/// Returned by [`Buffer::from_z85`] when the input isn't valid Z85.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Z85Error {
+    /// The length wasn't a multiple of 5, or one more than that for the padding count.
+    InvalidLength(usize),
+    /// A character that isn't in the Z85 alphabet was found at the given position.
+    InvalidSymbol {
+        /// The position of the character.
+        position: usize,
+    },
+    /// The block at the given position decodes to more than 4 bytes.
+    Overflow {
+        /// The position of the start of the block.
+        position: usize,
+    },
+    /// The padding count wasn't 1 to 3, or the padding bytes weren't zero.
+    InvalidPadding,
+}
+
+impl std::fmt::Display for Z85Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Z85Error::InvalidLength(len) => write!(f, "invalid z85: invalid length {len}"),
+            Z85Error::InvalidSymbol { position } => {
+                write!(f, "invalid z85: invalid symbol at {position}")
+            }
+            Z85Error::Overflow { position } => {
+                write!(f, "invalid z85: block at {position} is too large")
+            }
+            Z85Error::InvalidPadding => f.write_str("invalid z85: invalid padding"),
+        }
+    }
+}
+
+impl std::error::Error for Z85Error {}
+
+impl<T: AsRef<[u8]>> Buffer<T> {
+    /// Returns the contents of the buffer as Z85, which is denser than base64 but isn't understood by Roblox.
+    ///
+    /// Z85 only encodes multiples of 4 bytes, so other lengths are padded with zeros, and the number of padding
+    /// bytes is added to the end as a single digit. Padded output is one character longer than a multiple of 5.
+    pub fn to_z85(&self) -> String {
+        let data = self.0.as_ref();
+        let (blocks, rest) = data.as_chunks::<4>();
+
+        let mut z85 = String::with_capacity(data.len().div_ceil(4) * 5 + 1);
+        let mut encode = |block: [u8; 4]| {
+            let mut value = u32::from_be_bytes(block);
+            let mut chars = [0; 5];
+            for char in chars.iter_mut().rev() {
+                *char = ALPHABET[(value % 85) as usize];
+                value /= 85;
+            }
+            z85.push_str(std::str::from_utf8(&chars).expect("z85 is ascii"));
+        };
+
+        for &block in blocks {
+            encode(block);
+        }
+
+        if !rest.is_empty() {
+            let mut block = [0; 4];
+            block[..rest.len()].copy_from_slice(rest);
+            encode(block);
+            z85.push(char::from(b'0' + (4 - rest.len()) as u8));
+        }
+
+        z85
+    }
+}
+
+impl Buffer {
+    /// Decodes Z85 written by [`Buffer::to_z85`].
+    pub fn from_z85(z85: &str) -> Result<Self, Z85Error> {
+        let input = z85.as_bytes();
+
+        let (blocks, padding) = match input.len() % 5 {
+            0 => (input, 0),
+            1 => match input[input.len() - 1] {
+                padding @ b'1'..=b'3' => (&input[..input.len() - 1], (padding - b'0') as usize),
+                _ => return Err(Z85Error::InvalidPadding),
+            },
+            _ => return Err(Z85Error::InvalidLength(input.len())),
+        };
+
+        if padding > 0 && blocks.is_empty() {
+            return Err(Z85Error::InvalidPadding);
+        }
+
+        let mut data = Vec::with_capacity(blocks.len() / 5 * 4);
+        for (index, block) in blocks.chunks(5).enumerate() {
+            let mut value = 0u64;
+            for (offset, &char) in block.iter().enumerate() {
+                let digit = ALPHABET.iter().position(|&symbol| symbol == char).ok_or(
+                    Z85Error::InvalidSymbol {
+                        position: index * 5 + offset,
+                    },
+                )?;
+                value = value * 85 + digit as u64;
+            }
+
+            let value = u32::try_from(value).map_err(|_| Z85Error::Overflow {
+                position: index * 5,
+            })?;
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+
+        if data[data.len() - padding..].iter().any(|&byte| byte != 0) {
+            return Err(Z85Error::InvalidPadding);
+        }
+        data.truncate(data.len() - padding);
+
+        Ok(Buffer::new(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_z85_spec() {
+        let buffer = Buffer::new([0x86, 0x4f, 0xd2, 0x6f, 0xb5, 0x59, 0xf7, 0x5b]);
+        assert_eq!(buffer.to_z85(), "HelloWorld");
+        assert_eq!(Buffer::from_z85("HelloWorld").unwrap(), buffer);
+    }
+
+    #[test]
+    fn test_z85_round_trip() {
+        for len in 0..=9 {
+            let buffer = Buffer::new(
+                (0..len)
+                    .map(|byte: u8| byte.wrapping_mul(37))
+                    .collect::<Vec<u8>>(),
+            );
+            let z85 = buffer.to_z85();
+
+            assert_eq!(z85.len() % 5, if len % 4 == 0 { 0 } else { 1 }, "{len}");
+            assert_eq!(Buffer::from_z85(&z85).unwrap(), buffer, "{len}");
+        }
+
+        assert_eq!(Buffer::new("hello").to_z85(), "xK#0@zVx+q3");
+        assert_eq!(Buffer::new([0xff; 4]).to_z85(), "%nSc0");
+    }
+
+    #[test]
+    fn test_z85_invalid() {
+        assert_eq!(
+            Buffer::from_z85("Hello"),
+            Ok(Buffer::new([0x86, 0x4f, 0xd2, 0x6f]))
+        );
+        assert_eq!(Buffer::from_z85("Hell"), Err(Z85Error::InvalidLength(4)));
+        assert_eq!(
+            Buffer::from_z85("Hel\"o"),
+            Err(Z85Error::InvalidSymbol { position: 3 })
+        );
+        assert_eq!(
+            Buffer::from_z85("%nSc1"),
+            Err(Z85Error::Overflow { position: 0 })
+        );
+        assert_eq!(Buffer::from_z85("Hello4"), Err(Z85Error::InvalidPadding));
+        assert_eq!(Buffer::from_z85("Hello1"), Err(Z85Error::InvalidPadding));
+        assert_eq!(Buffer::from_z85("1"), Err(Z85Error::InvalidPadding));
+    }
+}